@@ -0,0 +1,143 @@
+mod output;
+
+use clap::{Parser, ValueEnum};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use diskvader_core::models::{ScanOptions, ScanProgress, ScanResults, SharedScanResults};
+use diskvader_core::report::{format_size, Report, ReportFormat};
+use diskvader_core::scanner::{self, NoopProgressSink, ProgressSink};
+use diskvader_core::snapshot::ScanSnapshot;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Html,
+}
+
+/// Analyse l'occupation disque d'un dossier sans interface graphique.
+#[derive(Parser)]
+#[command(name = "diskvader", version, about)]
+struct Cli {
+    /// Folder to scan
+    path: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "table")]
+    format: OutputFormat,
+
+    /// Number of entries in the largest files and folders sections
+    #[arg(short = 'n', long, default_value_t = 10)]
+    top: usize,
+
+    /// Entry name (e.g. node_modules) or path prefix to skip, may be repeated
+    #[arg(short, long = "exclude", value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Smallest file size kept in the largest files list (e.g. 100K, 10M, 1G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "100K")]
+    min_size: u64,
+
+    /// Write a snapshot that the desktop application can open
+    #[arg(short, long, value_name = "FILE")]
+    snapshot: Option<PathBuf>,
+
+    /// Do not print scan progress on stderr
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+// Progression affichée sur une seule ligne de stderr pour ne pas polluer la sortie JSON
+struct TerminalProgressSink;
+
+impl ProgressSink for TerminalProgressSink {
+    fn emit_progress(&self, progress: ScanProgress) {
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{} files, {} folders, {} scanned",
+            progress.files_analyzed,
+            progress.folders_analyzed,
+            format_size(progress.total_size),
+        );
+        let _ = stderr.flush();
+    }
+}
+
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split_at = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split_at);
+
+    let number: f64 = number.parse()
+        .map_err(|_| format!("invalid size: {}", value))?;
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1_000,
+        "M" | "MB" => 1_000_000,
+        "G" | "GB" => 1_000_000_000,
+        "T" | "TB" => 1_000_000_000_000,
+        "KI" | "KIB" => 1 << 10,
+        "MI" | "MIB" => 1 << 20,
+        "GI" | "GIB" => 1 << 30,
+        "TI" | "TIB" => 1 << 40,
+        _ => return Err(format!("unknown size unit: {}", unit)),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let options = ScanOptions {
+        exclude_patterns: cli.exclude.clone(),
+        min_file_size: cli.min_size,
+    };
+    let sink: Arc<dyn ProgressSink> = if cli.quiet {
+        Arc::new(NoopProgressSink)
+    } else {
+        Arc::new(TerminalProgressSink)
+    };
+
+    let scan_results: SharedScanResults = Arc::new(Mutex::new(ScanResults::default()));
+    let scan_path = cli.path.to_string_lossy().to_string();
+    let scan_outcome = scanner::run_scan(&scan_path, &options, &scan_results, sink);
+
+    if !cli.quiet {
+        eprintln!();
+    }
+
+    if let Err(e) = scan_outcome {
+        eprintln!("diskvader: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let results = scan_results.lock().unwrap();
+
+    if let Some(snapshot_path) = &cli.snapshot {
+        if let Err(e) = ScanSnapshot::from_results(&results).write_to_file(snapshot_path) {
+            eprintln!("diskvader: {}", e);
+            return ExitCode::FAILURE;
+        }
+        if !cli.quiet {
+            eprintln!("Snapshot written to {}", snapshot_path.display());
+        }
+    }
+
+    let report = Report::from_results(&results, cli.top, cli.top);
+    let rendered = match cli.format {
+        OutputFormat::Table => output::render_table(&report),
+        OutputFormat::Json => report.render(ReportFormat::Json),
+        OutputFormat::Csv => report.render(ReportFormat::Csv),
+        OutputFormat::Html => report.render(ReportFormat::Html),
+    };
+    // Une sortie fermée (`| head`) n'est pas une erreur de scan
+    let _ = writeln!(std::io::stdout(), "{}", rendered.trim_end());
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,53 @@
+use std::fmt::Write;
+
+use diskvader_core::report::{format_size, Report};
+
+pub fn render_table(report: &Report) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Scan of {}", report.scan_path);
+    let _ = writeln!(
+        out,
+        "{} files, {} folders, {} in {:.2} s",
+        report.total_files,
+        report.total_folders,
+        format_size(report.total_size),
+        report.scan_time,
+    );
+
+    let _ = writeln!(out, "\nLARGEST FILES");
+    let _ = writeln!(out, "{:>4}  {:>10}  {:<14}  PATH", "#", "SIZE", "TYPE");
+    for (index, file) in report.largest_files.iter().enumerate() {
+        let _ = writeln!(out, "{:>4}  {:>10}  {:<14}  {}", index + 1, format_size(file.size), file.file_type, file.path);
+    }
+
+    let _ = writeln!(out, "\nLARGEST FOLDERS");
+    let _ = writeln!(out, "{:>4}  {:>10}  {:>7}  {:>8}  PATH", "#", "SIZE", "SHARE", "FILES");
+    for (index, folder) in report.largest_folders.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{:>4}  {:>10}  {:>6.1}%  {:>8}  {}",
+            index + 1,
+            format_size(folder.size),
+            folder.percentage,
+            folder.file_count,
+            folder.path,
+        );
+    }
+
+    let _ = writeln!(out, "\nFILE TYPES");
+    let _ = writeln!(out, "{:<14}  {:>10}  {:>8}", "TYPE", "SIZE", "FILES");
+    for item in &report.file_types {
+        let _ = writeln!(out, "{:<14}  {:>10}  {:>8}", item.file_type, format_size(item.size), item.count);
+    }
+
+    let _ = writeln!(out, "\nCLEANUP SUGGESTIONS");
+    if report.cleanup_suggestions.is_empty() {
+        let _ = writeln!(out, "Nothing to suggest");
+    }
+    for item in &report.cleanup_suggestions {
+        let _ = writeln!(out, "{:<28}  {:>10}  {:>8} items", item.cleanup_type, format_size(item.size), item.count);
+    }
+
+    out.trim_end().to_string()
+}
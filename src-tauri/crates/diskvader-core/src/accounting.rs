@@ -0,0 +1,171 @@
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::{CommandError, ScanResults, ScannedFolder};
+use crate::openfiles;
+use crate::volumes::{self, allocated_size, device_of, FilesystemUsage};
+
+/// Bilan de l'espace occupé sur le volume du scan : ce que le scan explique et ce qu'il ne voit pas.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiskAccounting {
+    pub scan_path: PathBuf,
+    pub filesystem: FilesystemUsage,
+    /// La racine du scan est celle du volume ; sinon le reste du volume compte dans l'écart
+    pub covers_filesystem: bool,
+    /// Taille apparente des fichiers scannés
+    pub scanned_size: u64,
+    /// Blocs alloués aux fichiers et dossiers scannés du volume
+    pub scanned_allocated: u64,
+    pub excluded_size: u64,
+    pub excluded_count: u32,
+    /// Dossiers illisibles : leur contenu ne peut pas être mesuré sans privilèges et reste dans l'écart
+    pub unreadable_paths: Vec<PathBuf>,
+    pub unreadable_count: u32,
+    /// Blocs des dossiers illisibles eux-mêmes, seule part de leur occupation connue
+    pub unreadable_size: u64,
+    pub deleted_open_size: u64,
+    /// Occupé moins tout ce qui précède : métadonnées du système de fichiers, instantanés, contenu des dossiers illisibles...
+    pub unexplained: i64,
+}
+
+/// Chiffres du bilan repris par `get_scan_results`, gardés avec les résultats du scan.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AccountingSummary {
+    pub deleted_open_size: u64,
+    pub excluded_size: u64,
+    pub unreadable_count: u32,
+    pub unexplained: i64,
+}
+
+impl From<&DiskAccounting> for AccountingSummary {
+    fn from(accounting: &DiskAccounting) -> Self {
+        AccountingSummary {
+            deleted_open_size: accounting.deleted_open_size,
+            excluded_size: accounting.excluded_size,
+            unreadable_count: accounting.unreadable_count,
+            unexplained: accounting.unexplained,
+        }
+    }
+}
+
+/// Compare l'occupation du volume (`statvfs`) aux octets relevés par le dernier scan.
+pub fn disk_accounting(results: &ScanResults) -> Result<DiskAccounting, CommandError> {
+    let scan_path = PathBuf::from(&results.scan_path);
+    let filesystem = volumes::filesystem_usage(&scan_path)
+        .map_err(|e| CommandError::CannotGetMetadata(format!("{}: {}", scan_path.display(), e)))?;
+    let device = device_of(&scan_path);
+
+    let mut measured = HashSet::new();
+    let excluded_size: u64 = results.excluded_paths.iter()
+        .map(|path| measure_allocated(path, device, &mut measured))
+        .sum();
+    let unreadable_size: u64 = results.unreadable_paths.iter()
+        .map(|path| entry_allocated(path, device, &mut measured))
+        .sum();
+    let deleted_open_size = match device {
+        Some(device) => openfiles::deleted_open_files(Some(device)).total_size,
+        None => 0,
+    };
+
+    let explained = results.allocated_size + excluded_size + unreadable_size + deleted_open_size;
+    Ok(DiskAccounting {
+        covers_filesystem: is_mount_root(&scan_path),
+        scan_path,
+        filesystem,
+        scanned_size: results.total_size,
+        scanned_allocated: results.allocated_size,
+        excluded_size,
+        excluded_count: results.excluded_paths.len() as u32,
+        unreadable_paths: results.unreadable_paths.clone(),
+        unreadable_count: results.unreadable_paths.len() as u32,
+        unreadable_size,
+        deleted_open_size,
+        unexplained: filesystem.used as i64 - explained as i64,
+    })
+}
+
+/// Inodes du volume du scan face aux entrées relevées : un volume peut en manquer avec des octets libres.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InodeUsage {
+    pub scan_path: PathBuf,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inodes_free: u64,
+    /// Entrées trouvées sous la racine du scan, tous types confondus
+    pub scanned_entries: u64,
+}
+
+pub fn inode_usage(results: &ScanResults) -> Result<InodeUsage, CommandError> {
+    let scan_path = PathBuf::from(&results.scan_path);
+    let filesystem = volumes::filesystem_usage(&scan_path)
+        .map_err(|e| CommandError::CannotGetMetadata(format!("{}: {}", scan_path.display(), e)))?;
+    let scanned_entries = results.all_folders.iter()
+        .find(|folder| folder.path == scan_path)
+        .map_or(0, |root| u64::from(root.entry_count));
+
+    Ok(InodeUsage {
+        scan_path,
+        inodes_total: filesystem.inodes_total,
+        inodes_used: filesystem.inodes_used,
+        inodes_free: filesystem.inodes_free,
+        scanned_entries,
+    })
+}
+
+/// Dossiers du scan classés par nombre d'entrées, du plus peuplé au moins peuplé.
+pub fn folders_by_entries(results: &ScanResults, limit: usize) -> Vec<&ScannedFolder> {
+    let mut folders: Vec<&ScannedFolder> = results.all_folders.iter()
+        .filter(|folder| folder.entry_count > 0)
+        .collect();
+    folders.sort_by_key(|folder| Reverse(folder.entry_count));
+    folders.truncate(limit);
+    folders
+}
+
+// Blocs alloués sous `path`, sans quitter le volume ni compter deux fois un inode à plusieurs liens
+fn measure_allocated(path: &Path, device: Option<u64>, measured: &mut HashSet<(u64, u64)>) -> u64 {
+    let mut total = 0;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Some(metadata) = unmeasured_metadata(&current, device, measured) else {
+            continue;
+        };
+        total += allocated_size(&metadata);
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&current) {
+                stack.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        }
+    }
+    total
+}
+
+// Blocs de l'entrée seule, quand elle peut être lue par stat
+fn entry_allocated(path: &Path, device: Option<u64>, measured: &mut HashSet<(u64, u64)>) -> u64 {
+    unmeasured_metadata(path, device, measured).map_or(0, |metadata| allocated_size(&metadata))
+}
+
+// Métadonnées d'une entrée du volume pas encore mesurée
+fn unmeasured_metadata(path: &Path, device: Option<u64>, measured: &mut HashSet<(u64, u64)>) -> Option<fs::Metadata> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if device.is_some_and(|device| device != metadata.dev()) || !measured.insert((metadata.dev(), metadata.ino())) {
+            return None;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (device, &measured);
+    Some(metadata)
+}
+
+fn is_mount_root(path: &Path) -> bool {
+    match path.parent() {
+        Some(parent) => device_of(parent) != device_of(path),
+        None => true,
+    }
+}
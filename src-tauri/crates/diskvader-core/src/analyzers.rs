@@ -0,0 +1,156 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::artifacts;
+use crate::caches;
+use crate::thumbnails;
+use crate::models::{CleanupCandidate, CleanupPreviewGroup, CleanupSuggestionItem, FileTypeDistributionItem, ScannedFile, ScanResults};
+
+pub const EMPTY_FOLDERS: &str = "Empty Folders";
+
+pub const CHART_COLORS: [&str; 8] = [
+    "#3b82f6", "#10b981", "#f59e0b", "#8b5cf6",
+    "#ef4444", "#06b6d4", "#84cc16", "#f97316"
+];
+
+pub fn is_backup_file(file: &ScannedFile) -> bool {
+    let name_lower = file.name.to_lowercase();
+    name_lower.contains("backup") || name_lower.contains("bak") ||
+    name_lower.contains('~') || name_lower.ends_with(".old") ||
+    name_lower.contains("copy") || name_lower.contains("temp")
+}
+
+pub fn is_temporary_file(file: &ScannedFile) -> bool {
+    let name_lower = file.name.to_lowercase();
+    let path_lower = file.path.to_string_lossy().to_lowercase();
+    name_lower.starts_with("tmp") || name_lower.starts_with("temp") ||
+    path_lower.contains("/tmp/") || path_lower.contains("/temp/") ||
+    path_lower.contains("\\tmp\\") || path_lower.contains("\\temp\\") ||
+    file.extension == "tmp" || file.extension == "temp"
+}
+
+pub fn is_old_large_file(file: &ScannedFile, now: SystemTime) -> bool {
+    if file.size <= 100_000_000 {
+        return false;
+    }
+    // Date relevée au scan ; les imports (ncdu, instantanés) n'en ont pas et relisent le disque
+    let modified = match file.identity.as_ref() {
+        Some(identity) => identity.modified
+            .map(|nanos| SystemTime::UNIX_EPOCH + std::time::Duration::from_nanos(nanos.max(0) as u64)),
+        None => fs::metadata(&file.path).and_then(|metadata| metadata.modified()).ok(),
+    };
+    modified
+        .and_then(|modified| now.duration_since(modified).ok())
+        .map(|duration| duration.as_secs() > 365 * 24 * 3600)
+        .unwrap_or(false)
+}
+
+pub fn potential_duplicate_groups(files: &[ScannedFile]) -> Vec<Vec<&ScannedFile>> {
+    let mut size_groups: HashMap<u64, Vec<&ScannedFile>> = HashMap::new();
+    for file in files {
+        size_groups.entry(file.size).or_default().push(file);
+    }
+
+    size_groups.into_iter()
+        .filter(|(size, files)| files.len() > 1 && *size > 10_000)
+        .map(|(_, files)| files)
+        .collect()
+}
+
+fn file_candidate(file: &ScannedFile, reason: String) -> CleanupCandidate {
+    CleanupCandidate {
+        path: file.path.clone(),
+        size: file.size,
+        is_dir: false,
+        reason,
+        identity: file.identity.clone(),
+    }
+}
+
+/// Liste détaillée des éléments visés par chaque suggestion de nettoyage.
+pub fn cleanup_preview_groups(results: &ScanResults) -> Vec<CleanupPreviewGroup> {
+    let mut groups = Vec::new();
+
+    let mut duplicates = Vec::new();
+    for files in potential_duplicate_groups(&results.largest_files) {
+        let kept = files[0];
+        for file in files.iter().skip(1) {
+            let reason = format!("Same size ({} bytes) as {}", file.size, kept.path.display());
+            duplicates.push(file_candidate(file, reason));
+        }
+    }
+    groups.push(CleanupPreviewGroup::new("Potential Duplicate Files", "blue", duplicates));
+
+    let backups = results.largest_files.iter()
+        .filter(|f| is_backup_file(f))
+        .map(|f| file_candidate(f, "Name looks like a backup or copy".to_string()))
+        .collect();
+    groups.push(CleanupPreviewGroup::new("Backup Files", "green", backups));
+
+    let empty_folders = results.all_folders.iter()
+        .filter(|folder| folder.entry_count == 0)
+        .map(|folder| CleanupCandidate {
+            path: folder.path.clone(),
+            size: 0,
+            is_dir: true,
+            reason: "Folder has no entries".to_string(),
+            identity: None,
+        })
+        .collect();
+    groups.push(CleanupPreviewGroup::new(EMPTY_FOLDERS, "yellow", empty_folders));
+
+    let now = SystemTime::now();
+    let old_large_files = results.largest_files.iter()
+        .filter(|f| is_old_large_file(f, now))
+        .map(|f| file_candidate(f, "Larger than 100 MB and not modified for over a year".to_string()))
+        .collect();
+    groups.push(CleanupPreviewGroup::new("Old Large Files (>1 year)", "red", old_large_files));
+
+    let temporary_files = results.largest_files.iter()
+        .filter(|f| is_temporary_file(f))
+        .map(|f| file_candidate(f, "Temporary file name or location".to_string()))
+        .collect();
+    groups.push(CleanupPreviewGroup::new("Temporary Files", "orange", temporary_files));
+
+    let cache_owners = caches::cache_owners(results);
+    groups.push(CleanupPreviewGroup::new(caches::APPLICATION_CACHES, "cyan", caches::cleanup_candidates(&cache_owners)));
+
+    // Les miniatures sont lues sur le disque : seulement si le scan couvre le dossier de miniatures
+    let scan_path = Path::new(&results.scan_path);
+    if thumbnails::thumbnail_directories().iter().any(|directory| directory.starts_with(scan_path)) {
+        let mut orphans = thumbnails::orphaned_thumbnails();
+        orphans.retain(|orphan| orphan.path.starts_with(scan_path));
+        groups.push(CleanupPreviewGroup::new(thumbnails::ORPHANED_THUMBNAILS, "pink", thumbnails::cleanup_candidates(&orphans)));
+    }
+
+    let developer_projects = artifacts::developer_projects(results);
+    groups.push(CleanupPreviewGroup::new(artifacts::DEVELOPER_ARTIFACTS, "purple", artifacts::cleanup_candidates(&developer_projects)));
+
+    groups.retain(|group| group.count > 0);
+    groups.sort_by_key(|group| Reverse(group.size));
+
+    groups
+}
+
+pub fn cleanup_suggestions(results: &ScanResults) -> Vec<CleanupSuggestionItem> {
+    cleanup_preview_groups(results).iter().map(CleanupPreviewGroup::summary).collect()
+}
+
+pub fn file_type_distribution(results: &ScanResults) -> Vec<FileTypeDistributionItem> {
+    let mut distribution: Vec<FileTypeDistributionItem> = results.file_type_distribution.iter()
+        .enumerate()
+        .map(|(index, (file_type, (size, count)))| FileTypeDistributionItem {
+            file_type: file_type.clone(),
+            size: *size,
+            count: *count,
+            color: CHART_COLORS[index % CHART_COLORS.len()].to_string(),
+        })
+        .collect();
+
+    distribution.sort_by_key(|item| Reverse(item.size));
+
+    distribution
+}
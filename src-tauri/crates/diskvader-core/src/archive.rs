@@ -0,0 +1,537 @@
+use chrono::{DateTime, Datelike, Local, Timelike};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cleanup::CleanupBatch;
+use crate::models::CommandError;
+use crate::operation::{OperationProgress, OperationSink};
+
+// Intervalle minimal entre deux événements de progression pendant la copie d'un fichier
+const PROGRESS_STEP_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    #[default]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub source: PathBuf,
+    /// Chemin relatif dans l'archive, séparé par `/`
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub archive_path: PathBuf,
+    pub format: ArchiveFormat,
+    pub entries: Vec<ArchiveEntry>,
+    pub original_size: u64,
+    pub archive_size: u64,
+}
+
+/// Résultat de `compress_files` : l'archive vérifiée et, si demandé, le lot des originaux mis à la corbeille.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressionReport {
+    pub archive: ArchiveSummary,
+    pub trashed: Option<CleanupBatch>,
+    /// Espace libéré moins la taille de l'archive ; négatif si les originaux sont conservés
+    pub space_saved: i64,
+}
+
+impl CompressionReport {
+    pub fn new(archive: ArchiveSummary, trashed: Option<CleanupBatch>) -> Self {
+        let freed = trashed.as_ref().map(|batch| batch.freed_size).unwrap_or(0);
+        CompressionReport {
+            space_saved: freed as i64 - archive.archive_size as i64,
+            archive,
+            trashed,
+        }
+    }
+}
+
+// Contexte commun à l'écriture et à la relecture d'une archive
+struct ArchiveRun<'a> {
+    sink: &'a dyn OperationSink,
+    cancellation_flag: &'a AtomicBool,
+    operation: &'static str,
+    items_total: u32,
+    bytes_total: u64,
+    items_done: u32,
+    bytes_done: u64,
+    last_emitted: u64,
+}
+
+impl ArchiveRun<'_> {
+    fn check_cancelled(&self) -> io::Result<()> {
+        if self.cancellation_flag.load(Ordering::Relaxed) {
+            return Err(io::Error::other("operation cancelled"));
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, current_path: &str) {
+        self.last_emitted = self.bytes_done;
+        self.sink.emit_operation_progress(OperationProgress {
+            operation: self.operation.to_string(),
+            items_done: self.items_done,
+            items_total: self.items_total,
+            bytes_done: self.bytes_done,
+            bytes_total: self.bytes_total,
+            current_path: current_path.to_string(),
+        });
+    }
+
+    // Copie `reader` vers `writer` en calculant le SHA-256, avec progression et annulation
+    fn copy(&mut self, reader: &mut dyn Read, writer: &mut dyn Write, current_path: &str) -> io::Result<(u64, String)> {
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 256 * 1024];
+        let mut copied = 0u64;
+        loop {
+            self.check_cancelled()?;
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            writer.write_all(&buffer[..read])?;
+            copied += read as u64;
+            self.bytes_done += read as u64;
+            if self.bytes_done - self.last_emitted >= PROGRESS_STEP_BYTES {
+                self.emit(current_path);
+            }
+        }
+        self.items_done += 1;
+        self.emit(current_path);
+        Ok((copied, hex::encode(hasher.finalize())))
+    }
+}
+
+// Erreur d'E/S convertie en erreur de commande, l'annulation étant signalée comme telle
+fn io_error(error: io::Error, cancellation_flag: &AtomicBool) -> CommandError {
+    if cancellation_flag.load(Ordering::Relaxed) {
+        CommandError::Cancelled("Archive creation cancelled".to_string())
+    } else {
+        CommandError::InternalError(format!("Archive error: {}", error))
+    }
+}
+
+fn zip_error(error: zip::result::ZipError) -> io::Error {
+    match error {
+        zip::result::ZipError::Io(error) => error,
+        other => io::Error::other(other.to_string()),
+    }
+}
+
+// Les dates zip sont en heure locale, sans fuseau, et ne remontent pas avant 1980
+fn zip_date_time(modified: std::time::SystemTime) -> Option<zip::DateTime> {
+    let local: DateTime<Local> = modified.into();
+    zip::DateTime::from_date_and_time(
+        local.year().try_into().ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    ).ok()
+}
+
+/// Noms des fichiers dans l'archive, relatifs à leur dossier parent commun.
+pub fn entry_names(paths: &[PathBuf]) -> Vec<String> {
+    let mut common: Option<PathBuf> = None;
+    for path in paths {
+        let parent = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        common = Some(match common {
+            None => parent,
+            Some(common) => common.ancestors()
+                .find(|ancestor| parent.starts_with(ancestor))
+                .unwrap_or(Path::new(""))
+                .to_path_buf(),
+        });
+    }
+    let common = common.unwrap_or_default();
+
+    paths.iter()
+        .map(|path| {
+            let relative = path.strip_prefix(&common).unwrap_or(path);
+            relative.components()
+                .filter_map(|component| match component {
+                    std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+/// Crée une archive des fichiers `sources` à `destination`, puis la relit entièrement pour
+/// vérifier tailles et sommes SHA-256. L'archive partielle est supprimée en cas d'erreur ou d'annulation.
+pub fn create_archive(
+    sources: &[PathBuf],
+    destination: &Path,
+    format: ArchiveFormat,
+    sink: &dyn OperationSink,
+    cancellation_flag: &AtomicBool,
+) -> Result<ArchiveSummary, CommandError> {
+    if sources.is_empty() {
+        return Err(CommandError::InternalError("No files to archive".to_string()));
+    }
+    if destination.exists() {
+        return Err(CommandError::InternalError(format!("Destination already exists: {}", destination.display())));
+    }
+
+    let names = entry_names(sources);
+    let mut sizes = Vec::with_capacity(sources.len());
+    for source in sources {
+        let metadata = fs::metadata(source)
+            .map_err(|e| CommandError::CannotGetMetadata(format!("{}: {}", source.display(), e)))?;
+        if !metadata.is_file() {
+            return Err(CommandError::InternalError(format!("Not a regular file: {}", source.display())));
+        }
+        sizes.push(metadata.len());
+    }
+
+    let mut run = ArchiveRun {
+        sink,
+        cancellation_flag,
+        operation: "compress",
+        items_total: sources.len() as u32,
+        bytes_total: sizes.iter().sum(),
+        items_done: 0,
+        bytes_done: 0,
+        last_emitted: 0,
+    };
+
+    let written = write_archive(sources, &names, destination, format, &mut run)
+        .and_then(|entries| {
+            run.operation = "verify";
+            run.items_done = 0;
+            run.bytes_done = 0;
+            run.last_emitted = 0;
+            verify_archive(destination, format, &entries, &mut run).map(|()| entries)
+        });
+
+    let entries = match written {
+        Ok(entries) => entries,
+        Err(error) => {
+            let _ = fs::remove_file(destination);
+            return Err(io_error(error, cancellation_flag));
+        }
+    };
+
+    let archive_size = fs::metadata(destination)?.len();
+    log::info!("Archive {} created with {} files ({} bytes)", destination.display(), entries.len(), archive_size);
+
+    Ok(ArchiveSummary {
+        archive_path: destination.to_path_buf(),
+        format,
+        original_size: entries.iter().map(|entry| entry.size).sum(),
+        entries,
+        archive_size,
+    })
+}
+
+fn write_archive(sources: &[PathBuf], names: &[String], destination: &Path, format: ArchiveFormat, run: &mut ArchiveRun) -> io::Result<Vec<ArchiveEntry>> {
+    let file = BufWriter::new(File::create(destination)?);
+    let mut entries = Vec::with_capacity(sources.len());
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(file);
+            for (source, name) in sources.iter().zip(names) {
+                let mut input = File::open(source)?;
+                let metadata = input.metadata()?;
+                let mut options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(metadata.len() >= u32::MAX as u64);
+                if let Some(modified) = metadata.modified().ok().and_then(zip_date_time) {
+                    options = options.last_modified_time(modified);
+                }
+                zip.start_file(name.as_str(), options).map_err(zip_error)?;
+                let (size, sha256) = run.copy(&mut input, &mut zip, &source.to_string_lossy())?;
+                entries.push(ArchiveEntry { source: source.clone(), name: name.clone(), size, sha256 });
+            }
+            zip.finish().map_err(zip_error)?.flush()?;
+        }
+        ArchiveFormat::TarGz => {
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            let encoder = write_tar(encoder, sources, names, run, &mut entries)?;
+            encoder.finish()?.flush()?;
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::stream::write::Encoder::new(file, 0)?;
+            let encoder = write_tar(encoder, sources, names, run, &mut entries)?;
+            encoder.finish()?.flush()?;
+        }
+    }
+
+    Ok(entries)
+}
+
+// `tar` lit lui-même la source : ce lecteur calcule le SHA-256 et la progression au passage
+struct TrackedReader<'a, 'b> {
+    inner: File,
+    run: &'a mut ArchiveRun<'b>,
+    hasher: Sha256,
+    copied: u64,
+    current_path: String,
+}
+
+impl Read for TrackedReader<'_, '_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.run.check_cancelled()?;
+        let read = self.inner.read(buffer)?;
+        self.hasher.update(&buffer[..read]);
+        self.copied += read as u64;
+        self.run.bytes_done += read as u64;
+        if self.run.bytes_done - self.run.last_emitted >= PROGRESS_STEP_BYTES {
+            self.run.emit(&self.current_path);
+        }
+        Ok(read)
+    }
+}
+
+fn write_tar<W: Write>(writer: W, sources: &[PathBuf], names: &[String], run: &mut ArchiveRun, entries: &mut Vec<ArchiveEntry>) -> io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    for (source, name) in sources.iter().zip(names) {
+        let input = File::open(source)?;
+        let metadata = input.metadata()?;
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(&metadata);
+        header.set_size(metadata.len());
+
+        let mut reader = TrackedReader {
+            inner: input,
+            run: &mut *run,
+            hasher: Sha256::new(),
+            copied: 0,
+            current_path: source.to_string_lossy().to_string(),
+        };
+        builder.append_data(&mut header, name, &mut reader)?;
+        let (size, sha256) = (reader.copied, hex::encode(reader.hasher.finalize()));
+        if size != metadata.len() {
+            return Err(io::Error::other(format!("{} changed while being archived", source.display())));
+        }
+
+        run.items_done += 1;
+        run.emit(&source.to_string_lossy());
+        entries.push(ArchiveEntry { source: source.clone(), name: name.clone(), size, sha256 });
+    }
+    builder.into_inner()
+}
+
+fn verify_archive(destination: &Path, format: ArchiveFormat, entries: &[ArchiveEntry], run: &mut ArchiveRun) -> io::Result<()> {
+    let mut expected: HashMap<&str, &ArchiveEntry> = entries.iter().map(|entry| (entry.name.as_str(), entry)).collect();
+    let mut check = |name: &str, reader: &mut dyn Read, run: &mut ArchiveRun| -> io::Result<()> {
+        let entry = expected.remove(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Unexpected entry {} in archive", name)))?;
+        let (size, sha256) = run.copy(reader, &mut io::sink(), name)?;
+        if size != entry.size || sha256 != entry.sha256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Archive entry {} does not match {}", name, entry.source.display())));
+        }
+        Ok(())
+    };
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(destination)?).map_err(zip_error)?;
+            for index in 0..zip.len() {
+                let mut file = zip.by_index(index).map_err(zip_error)?;
+                let name = file.name().to_string();
+                check(&name, &mut file, run)?;
+            }
+        }
+        ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+            let input = File::open(destination)?;
+            let decoder: Box<dyn Read> = if format == ArchiveFormat::TarGz {
+                Box::new(flate2::read::GzDecoder::new(input))
+            } else {
+                Box::new(zstd::stream::read::Decoder::new(input)?)
+            };
+            let mut archive = tar::Archive::new(decoder);
+            for file in archive.entries()? {
+                let mut file = file?;
+                let name = file.path()?.to_string_lossy().to_string();
+                check(&name, &mut file, run)?;
+            }
+        }
+    }
+
+    if let Some(name) = expected.keys().next() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Entry {} is missing from the archive", name)));
+    }
+    Ok(())
+}
+
+/// Extrait l'entrée `name` d'une archive vers `writer`.
+pub fn extract_entry(archive_path: &Path, format: ArchiveFormat, name: &str, writer: &mut dyn Write) -> Result<u64, CommandError> {
+    let extract_error = |e: io::Error| CommandError::InternalError(format!("Cannot extract {} from {}: {}", name, archive_path.display(), e));
+    let input = File::open(archive_path).map_err(extract_error)?;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(input).map_err(|e| extract_error(zip_error(e)))?;
+            let mut file = zip.by_name(name).map_err(|e| extract_error(zip_error(e)))?;
+            io::copy(&mut file, writer).map_err(extract_error)
+        }
+        ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+            let decoder: Box<dyn Read> = if format == ArchiveFormat::TarGz {
+                Box::new(flate2::read::GzDecoder::new(input))
+            } else {
+                Box::new(zstd::stream::read::Decoder::new(input).map_err(extract_error)?)
+            };
+            let mut archive = tar::Archive::new(decoder);
+            for file in archive.entries().map_err(extract_error)? {
+                let mut file = file.map_err(extract_error)?;
+                if file.path().map_err(extract_error)?.to_string_lossy() == name {
+                    return io::copy(&mut file, writer).map_err(extract_error);
+                }
+            }
+            Err(CommandError::InternalError(format!("Entry {} not found in {}", name, archive_path.display())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation::NoopOperationSink;
+
+    fn temp_files(name: &str) -> (PathBuf, Vec<PathBuf>) {
+        let root = std::env::temp_dir().join(format!("diskvader-archive-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("photos/2024")).unwrap();
+        let sources = vec![root.join("photos/a.txt"), root.join("photos/2024/b.bin")];
+        fs::write(&sources[0], b"hello archive").unwrap();
+        fs::write(&sources[1], vec![7u8; 300_000]).unwrap();
+        (root, sources)
+    }
+
+    // Relit toutes les entrées de l'archive avec les bibliothèques de décodage
+    fn read_back(path: &Path, format: ArchiveFormat) -> Vec<(String, Vec<u8>)> {
+        let mut contents = Vec::new();
+        match format {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+                for index in 0..zip.len() {
+                    let mut file = zip.by_index(index).unwrap();
+                    let mut data = Vec::new();
+                    file.read_to_end(&mut data).unwrap();
+                    contents.push((file.name().to_string(), data));
+                }
+            }
+            ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+                let input = File::open(path).unwrap();
+                let decoder: Box<dyn Read> = if format == ArchiveFormat::TarGz {
+                    Box::new(flate2::read::GzDecoder::new(input))
+                } else {
+                    Box::new(zstd::stream::read::Decoder::new(input).unwrap())
+                };
+                let mut archive = tar::Archive::new(decoder);
+                for file in archive.entries().unwrap() {
+                    let mut file = file.unwrap();
+                    let name = file.path().unwrap().to_string_lossy().to_string();
+                    let mut data = Vec::new();
+                    file.read_to_end(&mut data).unwrap();
+                    contents.push((name, data));
+                }
+            }
+        }
+        contents.sort();
+        contents
+    }
+
+    #[test]
+    fn entry_names_are_relative_to_common_parent() {
+        let names = entry_names(&[PathBuf::from("/data/photos/a.jpg"), PathBuf::from("/data/photos/2024/b.jpg")]);
+        assert_eq!(names, vec!["a.jpg", "2024/b.jpg"]);
+
+        let names = entry_names(&[PathBuf::from("/data/a/x.txt"), PathBuf::from("/data/b/y.txt")]);
+        assert_eq!(names, vec!["a/x.txt", "b/y.txt"]);
+
+        assert_eq!(entry_names(&[PathBuf::from("/data/single.txt")]), vec!["single.txt"]);
+    }
+
+    #[test]
+    fn create_archive_round_trips_every_format() {
+        for format in [ArchiveFormat::Zip, ArchiveFormat::TarGz, ArchiveFormat::TarZst] {
+            let (root, sources) = temp_files(format.extension());
+            let destination = root.join(format!("out.{}", format.extension()));
+
+            let summary = create_archive(&sources, &destination, format, &NoopOperationSink, &AtomicBool::new(false)).unwrap();
+            assert_eq!(summary.entries.len(), 2);
+            assert_eq!(summary.original_size, 13 + 300_000);
+            assert_eq!(summary.archive_size, fs::metadata(&destination).unwrap().len());
+
+            assert_eq!(read_back(&destination, format), vec![
+                ("2024/b.bin".to_string(), vec![7u8; 300_000]),
+                ("a.txt".to_string(), b"hello archive".to_vec()),
+            ]);
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[test]
+    fn create_archive_refuses_existing_destination() {
+        let (root, sources) = temp_files("exists");
+        let destination = root.join("out.zip");
+        fs::write(&destination, b"keep me").unwrap();
+
+        assert!(create_archive(&sources, &destination, ArchiveFormat::Zip, &NoopOperationSink, &AtomicBool::new(false)).is_err());
+        assert_eq!(fs::read(&destination).unwrap(), b"keep me");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cancelled_archive_is_removed() {
+        let (root, sources) = temp_files("cancel");
+        let destination = root.join("out.tar.gz");
+
+        let result = create_archive(&sources, &destination, ArchiveFormat::TarGz, &NoopOperationSink, &AtomicBool::new(true));
+        assert!(matches!(result, Err(CommandError::Cancelled(_))));
+        assert!(!destination.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn extract_entry_reads_back_every_format() {
+        for format in [ArchiveFormat::Zip, ArchiveFormat::TarGz, ArchiveFormat::TarZst] {
+            let (root, sources) = temp_files(&format!("extract-{}", format.extension()));
+            let destination = root.join(format!("out.{}", format.extension()));
+            create_archive(&sources, &destination, format, &NoopOperationSink, &AtomicBool::new(false)).unwrap();
+
+            let mut content = Vec::new();
+            assert_eq!(extract_entry(&destination, format, "a.txt", &mut content).unwrap(), 13);
+            assert_eq!(content, b"hello archive");
+
+            let mut content = Vec::new();
+            assert_eq!(extract_entry(&destination, format, "2024/b.bin", &mut content).unwrap(), 300_000);
+            assert!(extract_entry(&destination, format, "missing.txt", &mut Vec::new()).is_err());
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+}
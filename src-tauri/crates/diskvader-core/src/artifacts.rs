@@ -0,0 +1,325 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::models::{datetime_from_nanos, CleanupCandidate, FileIdentity, ScanResults};
+
+pub const DEVELOPER_ARTIFACTS: &str = "Developer Artifacts";
+
+// Un projet sans activité depuis ce délai passe en tête de liste
+const STALE_AFTER_DAYS: i64 = 90;
+
+const NODE_MANIFESTS: &[&str] = &["package.json"];
+const PYTHON_MANIFESTS: &[&str] = &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt", "Pipfile", "tox.ini"];
+const GRADLE_MANIFESTS: &[&str] = &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"];
+
+/// Dossier de sortie régénérable, reconnu par son nom et le manifeste du projet qui le contient.
+struct ArtifactRule {
+    name: &'static str,
+    ecosystem: &'static str,
+    /// Manifestes attendus dans le dossier parent ; vide si le dossier se suffit à lui-même
+    manifests: &'static [&'static str],
+    /// Fichier attendu dans le dossier lui-même, à défaut de manifeste
+    marker: Option<&'static str>,
+}
+
+const RULES: &[ArtifactRule] = &[
+    ArtifactRule { name: "node_modules", ecosystem: "Node.js", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".next", ecosystem: "Next.js", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".nuxt", ecosystem: "Nuxt", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".svelte-kit", ecosystem: "SvelteKit", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".parcel-cache", ecosystem: "Parcel", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".turbo", ecosystem: "Turborepo", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: "dist", ecosystem: "Node.js", manifests: NODE_MANIFESTS, marker: None },
+    ArtifactRule { name: "target", ecosystem: "Rust", manifests: &["Cargo.toml"], marker: Some("CACHEDIR.TAG") },
+    ArtifactRule { name: "target", ecosystem: "Maven", manifests: &["pom.xml"], marker: None },
+    ArtifactRule { name: "build", ecosystem: "Gradle", manifests: GRADLE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".gradle", ecosystem: "Gradle", manifests: GRADLE_MANIFESTS, marker: None },
+    ArtifactRule { name: ".venv", ecosystem: "Python", manifests: PYTHON_MANIFESTS, marker: Some("pyvenv.cfg") },
+    ArtifactRule { name: "venv", ecosystem: "Python", manifests: PYTHON_MANIFESTS, marker: Some("pyvenv.cfg") },
+    ArtifactRule { name: ".tox", ecosystem: "Python", manifests: PYTHON_MANIFESTS, marker: None },
+    ArtifactRule { name: "__pycache__", ecosystem: "Python", manifests: &[], marker: None },
+    ArtifactRule { name: ".pytest_cache", ecosystem: "Python", manifests: &[], marker: None },
+    ArtifactRule { name: ".mypy_cache", ecosystem: "Python", manifests: &[], marker: None },
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DevArtifact {
+    pub path: PathBuf,
+    pub kind: String,
+    pub ecosystem: String,
+    pub size: u64,
+    pub file_count: u32,
+}
+
+/// Projet et dossiers régénérables qu'il contient.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DevProject {
+    pub path: PathBuf,
+    pub manifest: Option<String>,
+    /// Dernière modification d'un fichier du projet hors artefacts
+    pub last_activity: Option<DateTime<Utc>>,
+    pub stale: bool,
+    pub size: u64,
+    pub artifacts: Vec<DevArtifact>,
+}
+
+struct Detection {
+    rule: &'static ArtifactRule,
+    project: PathBuf,
+    manifest: Option<String>,
+}
+
+fn detect(directory: &Path) -> Option<Detection> {
+    let name = directory.file_name()?.to_str()?;
+    let parent = directory.parent()?;
+
+    RULES.iter()
+        .filter(|rule| rule.name == name)
+        .find_map(|rule| {
+            let manifest = rule.manifests.iter().find(|manifest| parent.join(manifest).is_file());
+            let has_marker = rule.marker.is_some_and(|marker| directory.join(marker).is_file());
+            if manifest.is_none() && !has_marker && !rule.manifests.is_empty() {
+                return None;
+            }
+            if rule.manifests.is_empty() {
+                // Caches dispersés dans les paquets : rattachés au projet Python le plus proche
+                let project = parent.ancestors()
+                    .find_map(|ancestor| PYTHON_MANIFESTS.iter()
+                        .find(|manifest| ancestor.join(manifest).is_file())
+                        .map(|manifest| (ancestor, manifest.to_string())));
+                let (project, manifest) = match project {
+                    Some((ancestor, manifest)) => (ancestor, Some(manifest)),
+                    None => (parent, None),
+                };
+                return Some(Detection {
+                    rule,
+                    project: project.to_path_buf(),
+                    manifest,
+                });
+            }
+            Some(Detection {
+                rule,
+                project: parent.to_path_buf(),
+                manifest: manifest.map(|manifest| manifest.to_string()),
+            })
+        })
+}
+
+// Dossier d'artefact le plus haut contenant `directory` : un node_modules imbriqué compte avec son parent
+fn artifact_root(directory: &Path, cache: &mut HashMap<PathBuf, Option<Detection>>) -> Option<PathBuf> {
+    let mut current = PathBuf::new();
+    for component in directory.components() {
+        current.push(component);
+        let detected = cache.entry(current.clone())
+            .or_insert_with(|| detect(&current))
+            .is_some();
+        if detected {
+            return Some(current);
+        }
+    }
+    None
+}
+
+/// Dossiers de build et de dépendances du scan, regroupés par projet.
+pub fn developer_projects(results: &ScanResults) -> Vec<DevProject> {
+    let mut cache = HashMap::new();
+    let mut artifacts: HashMap<PathBuf, DevArtifact> = HashMap::new();
+    let mut activity: HashMap<PathBuf, i64> = HashMap::new();
+
+    // Les tailles viennent des dossiers, qui comptent aussi les petits fichiers
+    for folder in &results.all_folders {
+        if artifact_root(&folder.path, &mut cache).as_ref() != Some(&folder.path) {
+            continue;
+        }
+        let Some(Some(detection)) = cache.get(&folder.path) else {
+            continue;
+        };
+        artifacts.insert(folder.path.clone(), DevArtifact {
+            path: folder.path.clone(),
+            kind: detection.rule.name.to_string(),
+            ecosystem: detection.rule.ecosystem.to_string(),
+            size: folder.size,
+            file_count: folder.file_count,
+        });
+    }
+
+    // Activité hors artefacts, remontée à chaque dossier parent
+    for file in &results.largest_files {
+        let Some(modified) = file.identity.as_ref().and_then(|identity| identity.modified) else {
+            continue;
+        };
+        if file.path.parent().and_then(|parent| artifact_root(parent, &mut cache)).is_some() {
+            continue;
+        }
+        for ancestor in file.path.ancestors().skip(1) {
+            let latest = activity.entry(ancestor.to_path_buf()).or_insert(modified);
+            *latest = (*latest).max(modified);
+        }
+    }
+
+    let mut projects: HashMap<PathBuf, DevProject> = HashMap::new();
+    for (root, artifact) in artifacts {
+        let Some(Some(detection)) = cache.get(&root) else {
+            continue;
+        };
+        let project = projects.entry(detection.project.clone()).or_insert_with(|| DevProject {
+            path: detection.project.clone(),
+            manifest: None,
+            last_activity: None,
+            stale: false,
+            size: 0,
+            artifacts: Vec::new(),
+        });
+        if project.manifest.is_none() {
+            project.manifest = detection.manifest.clone();
+        }
+        project.size += artifact.size;
+        project.artifacts.push(artifact);
+    }
+
+    let now = DateTime::<Utc>::from(SystemTime::now());
+    let mut projects: Vec<DevProject> = projects.into_values()
+        .map(|mut project| {
+            // Le manifeste témoigne aussi de l'activité, les petits fichiers n'étant pas relevés par le scan
+            let manifest_modified = project.manifest.as_ref()
+                .and_then(|manifest| FileIdentity::of_path(&project.path.join(manifest)).ok())
+                .and_then(|identity| identity.modified);
+            project.last_activity = activity.get(&project.path).copied().max(manifest_modified).map(datetime_from_nanos);
+            project.stale = project.last_activity
+                .map_or(true, |last_activity| (now - last_activity).num_days() > STALE_AFTER_DAYS);
+            project.artifacts.sort_by_key(|artifact| Reverse(artifact.size));
+            project
+        })
+        .collect();
+    projects.sort_by_key(|project| (Reverse(project.stale), Reverse(project.size)));
+    projects
+}
+
+/// Candidats de nettoyage : un par dossier d'artefact, dans l'ordre des projets.
+pub fn cleanup_candidates(projects: &[DevProject]) -> Vec<CleanupCandidate> {
+    projects.iter()
+        .flat_map(|project| {
+            let activity = project.last_activity
+                .map(|date| format!("last activity {}", date.format("%Y-%m-%d")))
+                .unwrap_or_else(|| "no recent activity".to_string());
+            project.artifacts.iter().map(move |artifact| CleanupCandidate {
+                path: artifact.path.clone(),
+                size: artifact.size,
+                is_dir: true,
+                reason: format!("{} {} of {} ({})", artifact.ecosystem, artifact.kind, project.path.display(), activity),
+                identity: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScannedFolder;
+    use std::fs;
+
+    fn temp_projects(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("diskvader-artifacts-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+
+    fn scanned(path: PathBuf, size: u64) -> ScannedFolder {
+        ScannedFolder {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            path,
+            size,
+            file_count: 1,
+            entry_count: 1,
+        }
+    }
+
+    #[test]
+    fn detect_requires_the_project_manifest() {
+        let root = temp_projects("manifest");
+        touch(&root.join("web/package.json"));
+        fs::create_dir_all(root.join("web/node_modules")).unwrap();
+        fs::create_dir_all(root.join("notes/node_modules")).unwrap();
+
+        let detection = detect(&root.join("web/node_modules")).unwrap();
+        assert_eq!((detection.rule.ecosystem, detection.project.clone()), ("Node.js", root.join("web")));
+        assert_eq!(detection.manifest.as_deref(), Some("package.json"));
+        assert!(detect(&root.join("notes/node_modules")).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn detect_accepts_a_marker_without_manifest() {
+        let root = temp_projects("marker");
+        touch(&root.join("moved/target/CACHEDIR.TAG"));
+        fs::create_dir_all(root.join("photos/target")).unwrap();
+
+        let detection = detect(&root.join("moved/target")).unwrap();
+        assert_eq!(detection.rule.ecosystem, "Rust");
+        assert_eq!(detection.manifest, None);
+        assert!(detect(&root.join("photos/target")).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn artifact_root_keeps_nested_node_modules_with_their_parent() {
+        let root = temp_projects("nested");
+        touch(&root.join("web/package.json"));
+        touch(&root.join("web/node_modules/left-pad/package.json"));
+        fs::create_dir_all(root.join("web/node_modules/left-pad/node_modules")).unwrap();
+
+        let mut cache = HashMap::new();
+        let nested = root.join("web/node_modules/left-pad/node_modules");
+        assert_eq!(artifact_root(&nested, &mut cache), Some(root.join("web/node_modules")));
+        assert_eq!(artifact_root(&root.join("web"), &mut cache), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn developer_projects_group_artifacts_by_project() {
+        let root = temp_projects("projects");
+        touch(&root.join("app/package.json"));
+        fs::create_dir_all(root.join("app/node_modules/lib/node_modules")).unwrap();
+        fs::create_dir_all(root.join("app/dist")).unwrap();
+        touch(&root.join("tool/pyproject.toml"));
+        fs::create_dir_all(root.join("tool/src/pkg/__pycache__")).unwrap();
+
+        let results = ScanResults {
+            all_folders: vec![
+                scanned(root.join("app"), 1_000),
+                scanned(root.join("app/node_modules"), 800),
+                scanned(root.join("app/node_modules/lib/node_modules"), 300),
+                scanned(root.join("app/dist"), 150),
+                scanned(root.join("tool/src/pkg/__pycache__"), 40),
+            ],
+            ..ScanResults::default()
+        };
+        let projects = developer_projects(&results);
+
+        let summary: Vec<(PathBuf, u64, Vec<&str>)> = projects.iter()
+            .map(|project| (
+                project.path.clone(),
+                project.size,
+                project.artifacts.iter().map(|artifact| artifact.kind.as_str()).collect(),
+            ))
+            .collect();
+        assert_eq!(summary, [
+            (root.join("app"), 950, vec!["node_modules", "dist"]),
+            (root.join("tool"), 40, vec!["__pycache__"]),
+        ]);
+        assert_eq!(projects[1].manifest.as_deref(), Some("pyproject.toml"));
+        assert_eq!(cleanup_candidates(&projects).len(), 3);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
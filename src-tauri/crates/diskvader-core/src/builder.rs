@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::histograms::FileHistograms;
+use crate::models::{ScanOptions, ScannedFile, ScannedFolder, ScanResults};
+use crate::scanner::{calculate_recursive_folder_data, get_file_type_cached};
+
+// Reconstruit un ScanResults à partir d'entrées qui ne viennent pas du scanner (imports)
+pub(crate) struct ResultsBuilder {
+    min_file_size: u64,
+    files: Vec<ScannedFile>,
+    // Taille, fichiers et entrées directs de chaque dossier
+    folder_data: HashMap<PathBuf, (u64, u32, u32)>,
+    directories: HashSet<PathBuf>,
+    file_type_distribution: HashMap<String, (u64, u32)>,
+    histograms: HashMap<PathBuf, HashMap<String, FileHistograms>>,
+    total_files: u32,
+    total_size: u64,
+    // Instant de l'import, référence des âges quand les dates des fichiers sont connues
+    imported_at: SystemTime,
+    dated: bool,
+}
+
+impl ResultsBuilder {
+    pub fn new() -> Self {
+        ResultsBuilder {
+            min_file_size: ScanOptions::default().min_file_size,
+            files: Vec::new(),
+            folder_data: HashMap::new(),
+            directories: HashSet::new(),
+            file_type_distribution: HashMap::new(),
+            histograms: HashMap::new(),
+            total_files: 0,
+            total_size: 0,
+            imported_at: SystemTime::now(),
+            dated: false,
+        }
+    }
+
+    pub fn add_directory(&mut self, path: &Path) {
+        self.directories.insert(path.to_path_buf());
+        self.folder_data.entry(path.to_path_buf()).or_insert((0, 0, 0));
+    }
+
+    pub fn add_file(&mut self, path: PathBuf, size: u64) {
+        self.add_dated_file(path, size, None);
+    }
+
+    // Fichier dont la date de modification est connue (find) : elle alimente les histogrammes d'âge
+    pub fn add_dated_file(&mut self, path: PathBuf, size: u64, modified: Option<SystemTime>) {
+        self.total_files += 1;
+        self.total_size += size;
+
+        if let Some(parent) = path.parent() {
+            self.directories.insert(parent.to_path_buf());
+            let entry = self.folder_data.entry(parent.to_path_buf()).or_insert((0, 0, 0));
+            entry.0 += size;
+            entry.1 += 1;
+            entry.2 += 1;
+        }
+
+        let extension = path.extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let file_type = get_file_type_cached(&extension);
+
+        let counter = self.file_type_distribution.entry(file_type.clone()).or_insert((0, 0));
+        counter.0 += size;
+        counter.1 += 1;
+        if let Some(parent) = path.parent() {
+            let histograms = self.histograms.entry(parent.to_path_buf()).or_default()
+                .entry(file_type.clone()).or_default();
+            match modified {
+                Some(modified) => {
+                    histograms.record_dated(size, Some(modified), None, self.imported_at);
+                    self.dated = true;
+                }
+                None => histograms.record_size(size),
+            }
+        }
+
+        if size > self.min_file_size {
+            self.files.push(ScannedFile {
+                name: path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                path,
+                size,
+                file_type,
+                extension,
+                identity: None,
+            });
+        }
+    }
+
+    // Octets connus d'un dossier sans le détail des fichiers qui les portent
+    pub fn add_aggregate(&mut self, dir_path: &Path, size: u64) {
+        self.total_size += size;
+        self.directories.insert(dir_path.to_path_buf());
+        self.folder_data.entry(dir_path.to_path_buf()).or_insert((0, 0, 0)).0 += size;
+
+        let counter = self.file_type_distribution.entry("Other".to_string()).or_insert((0, 0));
+        counter.0 += size;
+    }
+
+    pub fn finish(mut self, scan_path: &Path) -> ScanResults {
+        self.files.sort_by_key(|file| Reverse(file.size));
+
+        // Chaque sous-dossier est une entrée de son parent
+        for directory in &self.directories {
+            if let Some(parent) = directory.parent().filter(|parent| self.directories.contains(*parent)) {
+                self.folder_data.entry(parent.to_path_buf()).or_insert((0, 0, 0)).2 += 1;
+            }
+        }
+
+        // Même règle que le scanner : chaque dossier connu est enregistré, y compris vide
+        let folders: Vec<ScannedFolder> = self.folder_data.into_iter()
+            .map(|(path, (size, file_count, entry_count))| ScannedFolder {
+                name: path.file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .to_string(),
+                path,
+                size,
+                file_count,
+                entry_count,
+            })
+            .collect();
+        let folders = calculate_recursive_folder_data(folders);
+
+        ScanResults {
+            total_files: self.total_files,
+            total_folders: self.directories.len() as u32,
+            total_size: self.total_size,
+            scan_time: 0.0,
+            scan_path: scan_path.to_string_lossy().to_string(),
+            largest_files: self.files,
+            folders: folders.clone(),
+            all_folders: folders,
+            file_type_distribution: self.file_type_distribution,
+            histograms: self.histograms,
+            accounting: None,
+            scanned_at: self.dated.then(|| DateTime::<Utc>::from(self.imported_at)),
+            ..ScanResults::default()
+        }
+    }
+}
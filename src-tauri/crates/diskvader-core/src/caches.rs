@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::models::{CleanupCandidate, FileIdentity, ScanResults, ScannedFolder};
+
+pub const APPLICATION_CACHES: &str = "Application Caches";
+
+/// Cache connu, relatif au dossier personnel.
+struct KnownCache {
+    relative_path: &'static str,
+    owner: &'static str,
+    category: &'static str,
+}
+
+const KNOWN_CACHES: &[KnownCache] = &[
+    KnownCache { relative_path: ".cargo/registry", owner: "Cargo", category: "Package manager" },
+    KnownCache { relative_path: ".cargo/git", owner: "Cargo", category: "Package manager" },
+    KnownCache { relative_path: ".npm/_cacache", owner: "npm", category: "Package manager" },
+    KnownCache { relative_path: ".m2/repository", owner: "Maven", category: "Package manager" },
+    KnownCache { relative_path: ".gradle/caches", owner: "Gradle", category: "Package manager" },
+    KnownCache { relative_path: ".cache/pip", owner: "pip", category: "Package manager" },
+    KnownCache { relative_path: "Library/Caches/pip", owner: "pip", category: "Package manager" },
+    KnownCache { relative_path: ".cache/yarn", owner: "Yarn", category: "Package manager" },
+    KnownCache { relative_path: ".yarn/berry/cache", owner: "Yarn", category: "Package manager" },
+    KnownCache { relative_path: "Library/Caches/Yarn", owner: "Yarn", category: "Package manager" },
+    KnownCache { relative_path: "go/pkg/mod", owner: "Go modules", category: "Toolchain" },
+    KnownCache { relative_path: ".cache/go-build", owner: "Go build", category: "Toolchain" },
+    KnownCache { relative_path: "Library/Caches/go-build", owner: "Go build", category: "Toolchain" },
+    KnownCache { relative_path: ".cache/thumbnails", owner: "Thumbnails", category: "Thumbnails" },
+    KnownCache { relative_path: ".thumbnails", owner: "Thumbnails", category: "Thumbnails" },
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheLocation {
+    pub path: PathBuf,
+    pub size: u64,
+    pub file_count: u32,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Caches d'une même application ou d'un même outil.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheOwner {
+    pub owner: String,
+    pub category: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub age_days: Option<i64>,
+    pub locations: Vec<CacheLocation>,
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+/// Dossiers dont chaque sous-dossier est le cache d'une application : `$XDG_CACHE_HOME` (ou `~/.cache`) et `~/Library/Caches`.
+fn application_cache_roots(home: &Path) -> Vec<PathBuf> {
+    let xdg_cache = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".cache"));
+    vec![xdg_cache, home.join("Library/Caches")]
+}
+
+/// Caches de l'utilisateur présents dans le scan, par propriétaire, du plus volumineux au plus petit.
+pub fn cache_owners(results: &ScanResults) -> Vec<CacheOwner> {
+    let Some(home) = home_dir() else {
+        return Vec::new();
+    };
+    let cache_roots = application_cache_roots(&home);
+    owners_under(results, &home, &cache_roots)
+}
+
+fn owners_under(results: &ScanResults, home: &Path, cache_roots: &[PathBuf]) -> Vec<CacheOwner> {
+    let folders: HashMap<&Path, &ScannedFolder> = results.all_folders.iter()
+        .map(|folder| (folder.path.as_path(), folder))
+        .collect();
+
+    let mut found: Vec<(String, String, &ScannedFolder)> = KNOWN_CACHES.iter()
+        .filter_map(|known| {
+            let folder = folders.get(home.join(known.relative_path).as_path())?;
+            Some((known.owner.to_string(), known.category.to_string(), *folder))
+        })
+        .collect();
+    for root in cache_roots {
+        for folder in results.all_folders.iter().filter(|folder| folder.path.parent() == Some(root.as_path())) {
+            if !found.iter().any(|(_, _, known)| known.path == folder.path) {
+                found.push((folder.name.clone(), "Application".to_string(), folder));
+            }
+        }
+    }
+
+    // Dernière modification : la plus récente des fichiers relevés et du dossier lui-même
+    let mut latest: HashMap<&Path, DateTime<Utc>> = found.iter()
+        .filter_map(|(_, _, folder)| {
+            let modified = FileIdentity::of_path(&folder.path).ok()?.modified_at()?;
+            Some((folder.path.as_path(), modified))
+        })
+        .collect();
+    let cache_paths: HashSet<&Path> = found.iter().map(|(_, _, folder)| folder.path.as_path()).collect();
+    for file in &results.largest_files {
+        let Some(modified) = file.identity.as_ref().and_then(FileIdentity::modified_at) else {
+            continue;
+        };
+        if let Some(path) = file.path.ancestors().skip(1).find(|ancestor| cache_paths.contains(ancestor)) {
+            let entry = latest.entry(path).or_insert(modified);
+            *entry = (*entry).max(modified);
+        }
+    }
+
+    let now = Utc::now();
+    let mut owners: Vec<CacheOwner> = Vec::new();
+    for (owner, category, folder) in found {
+        let location = CacheLocation {
+            path: folder.path.clone(),
+            size: folder.size,
+            file_count: folder.file_count,
+            last_modified: latest.get(folder.path.as_path()).copied(),
+        };
+        let index = match owners.iter().position(|known| known.owner == owner) {
+            Some(index) => index,
+            None => {
+                owners.push(CacheOwner {
+                    owner,
+                    category,
+                    size: 0,
+                    last_modified: None,
+                    age_days: None,
+                    locations: Vec::new(),
+                });
+                owners.len() - 1
+            }
+        };
+        let entry = &mut owners[index];
+        entry.size += location.size;
+        entry.last_modified = entry.last_modified.max(location.last_modified);
+        entry.age_days = entry.last_modified.map(|modified| (now - modified).num_days());
+        entry.locations.push(location);
+    }
+
+    owners.sort_by_key(|owner| Reverse(owner.size));
+    owners
+}
+
+/// Candidats de nettoyage : un par emplacement de cache.
+pub fn cleanup_candidates(owners: &[CacheOwner]) -> Vec<CleanupCandidate> {
+    owners.iter()
+        .flat_map(|owner| {
+            let age = owner.age_days
+                .map(|days| format!("last written {} days ago", days))
+                .unwrap_or_else(|| "age unknown".to_string());
+            owner.locations.iter().map(move |location| CleanupCandidate {
+                path: location.path.clone(),
+                size: location.size,
+                is_dir: true,
+                reason: format!("{} cache ({}), {}", owner.owner, owner.category.to_lowercase(), age),
+                identity: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanned(path: &str, size: u64) -> ScannedFolder {
+        let path = PathBuf::from(path);
+        ScannedFolder {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            path,
+            size,
+            file_count: 1,
+            entry_count: 1,
+        }
+    }
+
+    fn owners(folders: Vec<ScannedFolder>) -> Vec<CacheOwner> {
+        let results = ScanResults { all_folders: folders, ..ScanResults::default() };
+        let home = Path::new("/home/test");
+        owners_under(&results, home, &[home.join(".cache"), home.join("Library/Caches")])
+    }
+
+    #[test]
+    fn known_caches_are_grouped_by_owner() {
+        let owners = owners(vec![
+            scanned("/home/test/.cargo/registry", 100),
+            scanned("/home/test/.cargo/git", 50),
+            scanned("/home/test/.npm", 70),
+            scanned("/home/test/project/.cargo/registry", 500),
+        ]);
+
+        assert_eq!(owners.len(), 1);
+        assert_eq!((owners[0].owner.as_str(), owners[0].category.as_str(), owners[0].size), ("Cargo", "Package manager", 150));
+        let paths: Vec<&Path> = owners[0].locations.iter().map(|location| location.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/home/test/.cargo/registry"), Path::new("/home/test/.cargo/git")]);
+    }
+
+    #[test]
+    fn application_caches_are_grouped_per_directory() {
+        let owners = owners(vec![
+            scanned("/home/test/.cache", 1_000),
+            scanned("/home/test/.cache/pip", 30),
+            scanned("/home/test/.cache/spotify", 200),
+            scanned("/home/test/.cache/spotify/Data", 150),
+            scanned("/home/test/Library/Caches/com.example.app", 80),
+        ]);
+
+        let summary: Vec<(&str, &str, u64)> = owners.iter()
+            .map(|owner| (owner.owner.as_str(), owner.category.as_str(), owner.size))
+            .collect();
+        // pip est reconnu comme gestionnaire de paquets et n'apparaît pas une seconde fois
+        assert_eq!(summary, [
+            ("spotify", "Application", 200),
+            ("com.example.app", "Application", 80),
+            ("pip", "Package manager", 30),
+        ]);
+        assert_eq!(cleanup_candidates(&owners).len(), 3);
+    }
+}
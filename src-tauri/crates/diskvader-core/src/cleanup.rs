@@ -0,0 +1,648 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::analyzers;
+use crate::dedup::{self, LinkInfo};
+use crate::models::{CleanupPreviewGroup, CommandError, FileIdentity, ScanResults};
+use crate::protected::ProtectedPaths;
+
+// Nombre de lots conservés dans le journal
+const MAX_JOURNAL_BATCHES: usize = 100;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupTarget {
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+    pub identity: Option<FileIdentity>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    pub permanent: bool,
+    /// Poursuit malgré un fichier modifié depuis le scan ; un lien symbolique substitué reste refusé
+    pub confirm_changed: bool,
+}
+
+/// Liste des éléments proposés au nettoyage, revue par l'utilisateur avant exécution.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupPreview {
+    pub preview_id: String,
+    pub scan_path: String,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+    pub groups: Vec<CleanupPreviewGroup>,
+}
+
+pub type SharedCleanupPreview = Arc<Mutex<Option<CleanupPreview>>>;
+
+impl CleanupPreview {
+    pub fn from_results(results: &ScanResults) -> Self {
+        let mut groups = analyzers::cleanup_preview_groups(results);
+        // Le scan ne relève pas l'identité des dossiers : elle est prise à l'aperçu pour être revérifiée à l'exécution
+        for item in groups.iter_mut().flat_map(|group| group.items.iter_mut()) {
+            if item.is_dir && item.identity.is_none() {
+                item.identity = FileIdentity::of_path(&item.path).ok();
+            }
+        }
+
+        CleanupPreview {
+            preview_id: format!("preview-{}", Utc::now().format("%Y%m%d-%H%M%S-%6f")),
+            scan_path: results.scan_path.clone(),
+            scanned_at: results.scanned_at,
+            generated_at: Utc::now(),
+            groups,
+        }
+    }
+
+    /// Vrai si l'aperçu a été calculé sur les résultats actuellement chargés.
+    pub fn matches(&self, results: &ScanResults) -> bool {
+        self.scan_path == results.scan_path && self.scanned_at == results.scanned_at
+    }
+
+    /// Cibles des groupes choisis, restreintes à `paths` si fourni ; chaque chemin n'apparaît qu'une fois,
+    /// et un élément situé dans un dossier déjà visé est laissé à ce dossier.
+    ///
+    /// La corbeille accepte les dossiers non vides : un dossier proposé parce que vide et qui ne l'est
+    /// plus est écarté et rapporté comme échec.
+    pub fn targets(&self, cleanup_types: &[String], paths: Option<&[PathBuf]>) -> (Vec<CleanupTarget>, Vec<CleanupFailure>) {
+        let mut seen = HashSet::new();
+        let mut targets = Vec::new();
+        let mut failures = Vec::new();
+        let items = self.groups.iter()
+            .filter(|group| cleanup_types.contains(&group.cleanup_type))
+            .flat_map(|group| group.items.iter().map(move |item| (group, item)))
+            .filter(|(_, item)| paths.map_or(true, |paths| paths.contains(&item.path)))
+            .filter(|(_, item)| seen.insert(item.path.clone()));
+
+        for (group, item) in items {
+            if group.cleanup_type == analyzers::EMPTY_FOLDERS && !is_empty_directory(&item.path) {
+                failures.push(CleanupFailure::new(&item.path, "Folder is no longer empty"));
+                continue;
+            }
+            targets.push(CleanupTarget {
+                path: item.path.clone(),
+                size: item.size,
+                is_dir: item.is_dir,
+                identity: item.identity.clone(),
+            });
+        }
+
+        // Sans cela, le dossier parti à la corbeille en premier ferait échouer ses descendants
+        let directories: Vec<PathBuf> = targets.iter()
+            .filter(|target| target.is_dir)
+            .map(|target| target.path.clone())
+            .collect();
+        targets.retain(|target| !directories.iter().any(|directory| target.path != *directory && target.path.starts_with(directory)));
+        (targets, failures)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupJournalEntry {
+    pub original_path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+    /// Identifiant de l'élément dans la corbeille (chemin du `.trashinfo` sous Linux),
+    /// absent pour une suppression définitive ou si la plateforme ne permet pas de le retrouver
+    pub trash_location: Option<String>,
+    pub restored: bool,
+    /// Doublon remplacé par un lien vers une autre copie plutôt que mis à la corbeille
+    #[serde(default)]
+    pub link: Option<LinkInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupFailure {
+    pub path: PathBuf,
+    pub error: String,
+    /// L'élément a changé depuis le scan : il peut être traité après confirmation
+    #[serde(default)]
+    pub needs_confirmation: bool,
+}
+
+impl CleanupFailure {
+    pub fn new(path: &Path, error: impl Into<String>) -> Self {
+        CleanupFailure {
+            path: path.to_path_buf(),
+            error: error.into(),
+            needs_confirmation: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupBatch {
+    pub batch_id: String,
+    pub created_at: DateTime<Utc>,
+    pub cleanup_types: Vec<String>,
+    pub permanent: bool,
+    pub freed_size: u64,
+    pub entries: Vec<CleanupJournalEntry>,
+    pub failures: Vec<CleanupFailure>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UndoReport {
+    pub batch_id: String,
+    pub restored: Vec<PathBuf>,
+    pub failures: Vec<CleanupFailure>,
+}
+
+/// Journal persistant des lots de nettoyage, utilisé pour annuler un lot mis à la corbeille.
+pub struct CleanupJournal {
+    path: Option<PathBuf>,
+    batches: Vec<CleanupBatch>,
+}
+
+pub type SharedCleanupJournal = Arc<Mutex<CleanupJournal>>;
+
+impl CleanupJournal {
+    pub fn in_memory() -> Self {
+        CleanupJournal {
+            path: None,
+            batches: Vec::new(),
+        }
+    }
+
+    /// Charge le journal ; un fichier illisible est mis de côté plutôt qu'écrasé par le prochain lot.
+    pub fn load(path: PathBuf) -> Self {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return CleanupJournal { path: Some(path), batches: Vec::new() },
+        };
+        match serde_json::from_str(&content) {
+            Ok(batches) => CleanupJournal { path: Some(path), batches },
+            Err(e) => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let aside = path.with_file_name(format!("{}.corrupt-{}", name, Utc::now().format("%Y%m%d-%H%M%S")));
+                match fs::rename(&path, &aside) {
+                    Ok(()) => {
+                        log::error!("Cleanup journal {} is unreadable ({}), moved to {}", path.display(), e, aside.display());
+                        CleanupJournal { path: Some(path), batches: Vec::new() }
+                    }
+                    Err(rename_error) => {
+                        // Sans copie de côté, le journal n'est plus enregistré pour ne pas perdre l'ancien
+                        log::error!("Cleanup journal {} is unreadable ({}) and cannot be moved aside ({}), new batches will not be saved",
+                            path.display(), e, rename_error);
+                        CleanupJournal::in_memory()
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn batches(&self) -> &[CleanupBatch] {
+        &self.batches
+    }
+
+    pub fn batch_mut(&mut self, batch_id: &str) -> Option<&mut CleanupBatch> {
+        self.batches.iter_mut().find(|batch| batch.batch_id == batch_id)
+    }
+
+    pub fn record(&mut self, batch: CleanupBatch) -> Result<(), CommandError> {
+        self.batches.push(batch);
+        if self.batches.len() > MAX_JOURNAL_BATCHES {
+            let excess = self.batches.len() - MAX_JOURNAL_BATCHES;
+            self.batches.drain(0..excess);
+        }
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<(), CommandError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.batches)
+            .map_err(|e| CommandError::InternalError(format!("Failed to serialize cleanup journal: {}", e)))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+fn is_empty_directory(path: &Path) -> bool {
+    fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(false)
+}
+
+fn new_batch_id() -> String {
+    format!("cleanup-{}", Utc::now().format("%Y%m%d-%H%M%S-%6f"))
+}
+
+/// Revérifie qu'une cible est toujours celle relevée au scan.
+pub fn verify_target(target: &CleanupTarget, confirm_changed: bool) -> Result<(), CleanupFailure> {
+    let metadata = fs::symlink_metadata(&target.path)
+        .map_err(|e| CleanupFailure::new(&target.path, e.to_string()))?;
+    let current = FileIdentity::from_metadata(&metadata);
+
+    let expected_symlink = target.identity.as_ref().map(|identity| identity.is_symlink).unwrap_or(false);
+    if current.is_symlink && !expected_symlink {
+        return Err(CleanupFailure::new(&target.path, "A symbolic link was found in place of the scanned entry"));
+    }
+    if target.is_dir && !metadata.is_dir() {
+        return Err(CleanupFailure::new(&target.path, "Entry is no longer a directory"));
+    }
+    if confirm_changed {
+        return Ok(());
+    }
+
+    let error = match &target.identity {
+        Some(expected) => {
+            let differences = expected.differences(&current);
+            if differences.is_empty() {
+                return Ok(());
+            }
+            let kind = if target.is_dir { "Folder" } else { "File" };
+            format!("{} changed since the scan: {}", kind, differences.join(", "))
+        }
+        None => "No identity was recorded for this entry during the scan".to_string(),
+    };
+    Err(CleanupFailure {
+        needs_confirmation: true,
+        ..CleanupFailure::new(&target.path, error)
+    })
+}
+
+/// Met les cibles à la corbeille (ou les supprime définitivement si `permanent`) après avoir
+/// écarté les chemins protégés et vérifié leur identité, et retourne le lot à enregistrer dans le journal.
+pub fn execute_cleanup(targets: &[CleanupTarget], cleanup_types: Vec<String>, options: CleanupOptions, protected: &ProtectedPaths) -> CleanupBatch {
+    let mut entries = Vec::new();
+    let mut failures = Vec::new();
+
+    for target in targets {
+        if let Some(reason) = protected.violation(&target.path) {
+            log::warn!("Refusing to clean protected path {:?}: {}", target.path, reason);
+            failures.push(CleanupFailure::new(&target.path, format!("Protected path: {}", reason)));
+            continue;
+        }
+        if let Err(failure) = verify_target(target, options.confirm_changed) {
+            log::warn!("Skipping {:?}: {}", target.path, failure.error);
+            failures.push(failure);
+            continue;
+        }
+
+        let outcome = if options.permanent {
+            remove_permanently(target)
+        } else {
+            trash::delete(&target.path).map_err(|e| e.to_string())
+        };
+
+        match outcome {
+            Ok(()) => {
+                log::info!("Cleaned {:?} ({} bytes)", target.path, target.size);
+                entries.push(CleanupJournalEntry {
+                    original_path: target.path.clone(),
+                    size: target.size,
+                    is_dir: target.is_dir,
+                    trash_location: None,
+                    restored: false,
+                    link: None,
+                });
+            }
+            Err(error) => {
+                log::warn!("Failed to clean {:?}: {}", target.path, error);
+                failures.push(CleanupFailure::new(&target.path, error));
+            }
+        }
+    }
+
+    if !options.permanent {
+        locate_trashed_entries(&mut entries);
+    }
+
+    CleanupBatch {
+        batch_id: new_batch_id(),
+        created_at: Utc::now(),
+        cleanup_types,
+        permanent: options.permanent,
+        freed_size: entries.iter().map(|entry| entry.size).sum(),
+        entries,
+        failures,
+    }
+}
+
+fn remove_permanently(target: &CleanupTarget) -> Result<(), String> {
+    let result = if target.is_dir {
+        fs::remove_dir_all(&target.path)
+    } else {
+        fs::remove_file(&target.path)
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn locate_trashed_entries(entries: &mut [CleanupJournalEntry]) {
+    let mut items = match trash::os_limited::list() {
+        Ok(items) => items,
+        Err(e) => {
+            log::warn!("Cannot list trash to journal cleanup: {}", e);
+            return;
+        }
+    };
+    // Le plus récent d'abord : un même chemin peut déjà avoir été mis à la corbeille auparavant
+    items.sort_by_key(|item| std::cmp::Reverse(item.time_deleted));
+
+    for entry in entries.iter_mut() {
+        if let Some(position) = items.iter().position(|item| item.original_path() == entry.original_path) {
+            let item = items.remove(position);
+            entry.trash_location = Some(item.id.to_string_lossy().to_string());
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn locate_trashed_entries(_entries: &mut [CleanupJournalEntry]) {}
+
+/// Annule un lot : les éléments mis à la corbeille y sont repris, les doublons liés retrouvent leur propre copie.
+pub fn undo_batch(batch: &mut CleanupBatch) -> Result<UndoReport, CommandError> {
+    if batch.permanent {
+        return Err(CommandError::InternalError(format!("Batch {} was permanently deleted and cannot be undone", batch.batch_id)));
+    }
+
+    let mut report = UndoReport {
+        batch_id: batch.batch_id.clone(),
+        ..UndoReport::default()
+    };
+
+    for entry in batch.entries.iter_mut().filter(|entry| !entry.restored && entry.link.is_some()) {
+        match dedup::unlink_entry(entry) {
+            Ok(()) => {
+                entry.restored = true;
+                report.restored.push(entry.original_path.clone());
+            }
+            Err(error) => report.failures.push(CleanupFailure::new(&entry.original_path, error)),
+        }
+    }
+
+    if batch.entries.iter().any(|entry| !entry.restored && entry.link.is_none()) {
+        restore_trashed_entries(batch, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore_trashed_entries(batch: &mut CleanupBatch, report: &mut UndoReport) -> Result<(), CommandError> {
+    let mut items = trash::os_limited::list()
+        .map_err(|e| CommandError::InternalError(format!("Cannot list trash: {}", e)))?;
+
+    for entry in batch.entries.iter_mut().filter(|entry| !entry.restored && entry.link.is_none()) {
+        let position = entry.trash_location.as_ref().and_then(|location| {
+            items.iter().position(|item| item.id.to_string_lossy() == location.as_str())
+        });
+        let Some(position) = position else {
+            report.failures.push(CleanupFailure::new(&entry.original_path, "Item is no longer in the trash"));
+            continue;
+        };
+
+        if Path::new(&entry.original_path).exists() {
+            report.failures.push(CleanupFailure::new(&entry.original_path, "A file already exists at the original location"));
+            continue;
+        }
+
+        let item = items.remove(position);
+        match trash::os_limited::restore_all(vec![item]) {
+            Ok(()) => {
+                entry.restored = true;
+                report.restored.push(entry.original_path.clone());
+            }
+            Err(e) => report.failures.push(CleanupFailure::new(&entry.original_path, e.to_string())),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore_trashed_entries(batch: &mut CleanupBatch, _report: &mut UndoReport) -> Result<(), CommandError> {
+    Err(CommandError::InternalError(format!("Restoring batch {} from the trash is not supported on this platform", batch.batch_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CleanupCandidate;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("diskvader-cleanup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn target(path: &Path) -> CleanupTarget {
+        let metadata = fs::symlink_metadata(path).unwrap();
+        CleanupTarget {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            is_dir: metadata.is_dir(),
+            identity: Some(FileIdentity::from_metadata(&metadata)),
+        }
+    }
+
+    fn candidate(path: &str, is_dir: bool) -> CleanupCandidate {
+        CleanupCandidate {
+            path: PathBuf::from(path),
+            size: 10,
+            is_dir,
+            reason: String::new(),
+            identity: None,
+        }
+    }
+
+    fn clean(targets: &[CleanupTarget], permanent: bool) -> CleanupBatch {
+        let options = CleanupOptions { permanent, confirm_changed: false };
+        execute_cleanup(targets, vec!["Test".to_string()], options, &ProtectedPaths::default())
+    }
+
+    #[test]
+    fn targets_leave_descendants_to_their_directory() {
+        let preview = CleanupPreview {
+            preview_id: "preview".to_string(),
+            scan_path: "/data".to_string(),
+            scanned_at: None,
+            generated_at: Utc::now(),
+            groups: vec![
+                CleanupPreviewGroup::new("Caches", "cyan", vec![candidate("/data/.cache/app", true)]),
+                CleanupPreviewGroup::new("Temporary Files", "orange", vec![
+                    candidate("/data/.cache/app/tmp.bin", false),
+                    candidate("/data/.cache/application.tmp", false),
+                    candidate("/data/.cache/app", true),
+                ]),
+            ],
+        };
+        let types = ["Caches".to_string(), "Temporary Files".to_string()];
+
+        let (targets, failures) = preview.targets(&types, None);
+        assert!(failures.is_empty());
+        let paths: Vec<&Path> = targets.iter().map(|target| target.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/data/.cache/app"), Path::new("/data/.cache/application.tmp")]);
+
+        // Sans le dossier, son contenu redevient une cible
+        let (targets, _) = preview.targets(&types[1..], Some(&[PathBuf::from("/data/.cache/app/tmp.bin")]));
+        assert_eq!(targets.len(), 1);
+    }
+
+    #[test]
+    fn permanent_cleanup_removes_files_and_folders() {
+        let root = temp_dir("permanent");
+        fs::write(root.join("a.log"), b"old log").unwrap();
+        fs::create_dir_all(root.join("cache/sub")).unwrap();
+        fs::write(root.join("cache/sub/data"), b"cached").unwrap();
+
+        let mut batch = clean(&[target(&root.join("a.log")), target(&root.join("cache"))], true);
+        assert!(batch.failures.is_empty(), "{:?}", batch.failures);
+        assert!(batch.permanent);
+        assert_eq!(batch.entries.len(), 2);
+        assert!(batch.entries.iter().all(|entry| entry.trash_location.is_none()));
+        assert!(!root.join("a.log").exists());
+        assert!(!root.join("cache").exists());
+
+        assert!(undo_batch(&mut batch).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn trashed_cleanup_can_be_undone() {
+        let root = temp_dir("trash");
+        fs::write(root.join("a.log"), b"old log").unwrap();
+
+        let mut batch = clean(&[target(&root.join("a.log"))], false);
+        assert!(batch.failures.is_empty(), "{:?}", batch.failures);
+        assert!(!batch.permanent);
+        assert!(!root.join("a.log").exists());
+        assert!(batch.entries[0].trash_location.is_some());
+
+        let report = undo_batch(&mut batch).unwrap();
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.restored, [root.join("a.log")]);
+        assert_eq!(fs::read(root.join("a.log")).unwrap(), b"old log");
+        assert!(batch.entries[0].restored);
+
+        // Un second undo ne reprend rien
+        assert!(undo_batch(&mut batch).unwrap().restored.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_duplicates_can_be_undone() {
+        let root = temp_dir("undo-link");
+        fs::write(root.join("kept.bin"), b"same content").unwrap();
+        fs::write(root.join("copy.bin"), b"same content").unwrap();
+        let groups = [vec![target(&root.join("kept.bin")), target(&root.join("copy.bin"))]];
+        let mut batch = dedup::link_duplicates(&groups, dedup::LinkMode::Hardlink, false, &ProtectedPaths::default());
+        assert_eq!(batch.entries.len(), 1);
+
+        let report = undo_batch(&mut batch).unwrap();
+        assert_eq!(report.restored, [root.join("copy.bin")]);
+        let kept = FileIdentity::of_path(&root.join("kept.bin")).unwrap();
+        let copy = FileIdentity::of_path(&root.join("copy.bin")).unwrap();
+        assert_ne!(kept.inode, copy.inode);
+        assert_eq!(fs::read(root.join("copy.bin")).unwrap(), b"same content");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_swapped_in_is_rejected() {
+        let root = temp_dir("symlink");
+        fs::write(root.join("a.log"), b"old log").unwrap();
+        fs::write(root.join("precious.txt"), b"keep me").unwrap();
+        let scanned = target(&root.join("a.log"));
+
+        fs::remove_file(root.join("a.log")).unwrap();
+        std::os::unix::fs::symlink(root.join("precious.txt"), root.join("a.log")).unwrap();
+
+        let failure = verify_target(&scanned, true).unwrap_err();
+        assert!(!failure.needs_confirmation);
+        let batch = clean(&[scanned], true);
+        assert!(batch.entries.is_empty());
+        assert_eq!(batch.failures.len(), 1);
+        assert!(fs::symlink_metadata(root.join("a.log")).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read(root.join("precious.txt")).unwrap(), b"keep me");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn replaced_file_needs_confirmation() {
+        let root = temp_dir("identity");
+        fs::write(root.join("a.log"), b"old log").unwrap();
+        let scanned = target(&root.join("a.log"));
+
+        // Nouveau fichier au même chemin : autre inode, autre taille
+        fs::remove_file(root.join("a.log")).unwrap();
+        fs::write(root.join("other.tmp"), b"something new").unwrap();
+        fs::rename(root.join("other.tmp"), root.join("a.log")).unwrap();
+
+        let failure = verify_target(&scanned, false).unwrap_err();
+        assert!(failure.needs_confirmation);
+        assert!(failure.error.starts_with("File changed since the scan"));
+        assert!(verify_target(&scanned, true).is_ok());
+
+        let batch = clean(std::slice::from_ref(&scanned), true);
+        assert!(batch.entries.is_empty());
+        assert_eq!(fs::read(root.join("a.log")).unwrap(), b"something new");
+
+        let without_identity = CleanupTarget { identity: None, ..scanned };
+        assert!(verify_target(&without_identity, false).unwrap_err().needs_confirmation);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn folder_replaced_by_a_file_is_rejected() {
+        let root = temp_dir("folder");
+        fs::create_dir_all(root.join("cache")).unwrap();
+        let scanned = target(&root.join("cache"));
+        fs::remove_dir(root.join("cache")).unwrap();
+        fs::write(root.join("cache"), b"now a file").unwrap();
+
+        let failure = verify_target(&scanned, true).unwrap_err();
+        assert_eq!(failure.error, "Entry is no longer a directory");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn corrupt_journal_is_moved_aside() {
+        let root = temp_dir("journal");
+        let path = root.join("cleanup-journal.json");
+        fs::write(&path, b"{ not json").unwrap();
+
+        let mut journal = CleanupJournal::load(path.clone());
+        assert!(journal.batches().is_empty());
+        let aside: Vec<PathBuf> = fs::read_dir(&root).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|entry| entry.to_string_lossy().contains(".corrupt-"))
+            .collect();
+        assert_eq!(aside.len(), 1);
+        assert_eq!(fs::read(&aside[0]).unwrap(), b"{ not json");
+
+        let batch = clean(&[], true);
+        journal.record(batch.clone()).unwrap();
+        let reloaded = CleanupJournal::load(path);
+        assert_eq!(reloaded.batches().len(), 1);
+        assert_eq!(reloaded.batches()[0].batch_id, batch.batch_id);
+        assert_eq!(fs::read(&aside[0]).unwrap(), b"{ not json");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
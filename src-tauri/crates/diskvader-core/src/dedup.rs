@@ -0,0 +1,478 @@
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::analyzers;
+use crate::cleanup::{self, CleanupBatch, CleanupFailure, CleanupJournalEntry, CleanupTarget};
+use crate::models::{CommandError, FileIdentity, ScanResults, ScannedFile};
+use crate::offload::sha256_file;
+use crate::protected::ProtectedPaths;
+
+pub const LINKED_DUPLICATES: &str = "Linked Duplicates";
+
+// Taille du bloc comparé avant de hacher un fichier en entier
+const PREFIX_BYTES: u64 = 64 * 1024;
+
+/// Manière de faire partager son contenu à un doublon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// Reflink si le système de fichiers le permet, sinon lien physique
+    #[default]
+    Auto,
+    Hardlink,
+    Reflink,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub id: String,
+    pub path: PathBuf,
+}
+
+/// Fichiers dont le contenu a été comparé par SHA-256 ; les chemins pointant déjà vers le même inode n'apparaissent qu'une fois.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub sha256: String,
+    pub size: u64,
+    pub files: Vec<DuplicateFile>,
+}
+
+/// Lien créé à la place d'un doublon, conservé dans le journal pour pouvoir le défaire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkInfo {
+    pub source: PathBuf,
+    pub mode: LinkMode,
+    pub sha256: String,
+    /// Permissions du doublon remplacé (mode Unix)
+    pub permissions: Option<u32>,
+}
+
+/// Regroupe par contenu les fichiers de même taille du scan. Un premier bloc est comparé d'abord :
+/// seuls les fichiers dont le début est identique sont hachés en entier.
+pub fn verified_duplicate_groups(results: &ScanResults) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+
+    for files in analyzers::potential_duplicate_groups(&results.largest_files) {
+        let size = files[0].size;
+        let mut by_prefix: HashMap<String, Vec<&ScannedFile>> = HashMap::new();
+        let mut seen_inodes = Vec::new();
+        for file in files {
+            if let Some(identity) = &file.identity {
+                if seen_inodes.contains(&(identity.device, identity.inode)) {
+                    continue;
+                }
+                seen_inodes.push((identity.device, identity.inode));
+            }
+            match sha256_prefix(&file.path) {
+                Ok(prefix) => by_prefix.entry(prefix).or_default().push(file),
+                Err(e) => log::warn!("Cannot hash {:?}: {}", file.path, e),
+            }
+        }
+
+        let mut by_hash: HashMap<String, Vec<DuplicateFile>> = HashMap::new();
+        for file in by_prefix.into_values().filter(|files| files.len() > 1).flatten() {
+            match sha256_file(&file.path) {
+                Ok(sha256) => by_hash.entry(sha256).or_default().push(DuplicateFile {
+                    id: results.file_id(file),
+                    path: file.path.clone(),
+                }),
+                Err(e) => log::warn!("Cannot hash {:?}: {}", file.path, e),
+            }
+        }
+
+        groups.extend(by_hash.into_iter()
+            .filter(|(_, files)| files.len() > 1)
+            .map(|(sha256, files)| DuplicateGroup { sha256, size, files }));
+    }
+
+    groups.sort_by_key(|group| Reverse(group.size * (group.files.len() as u64 - 1)));
+    groups
+}
+
+// SHA-256 du premier bloc du fichier, pour écarter vite les fichiers de même taille qui diffèrent
+fn sha256_prefix(path: &Path) -> io::Result<String> {
+    let mut prefix = Vec::with_capacity(PREFIX_BYTES as usize);
+    File::open(path)?.take(PREFIX_BYTES).read_to_end(&mut prefix)?;
+    Ok(hex::encode(Sha256::digest(&prefix)))
+}
+
+/// Remplace les doublons de chaque groupe par un lien vers son premier fichier, conservé tel quel.
+/// Les contenus sont recomparés juste avant, et chaque remplacement passe par un renommage atomique.
+pub fn link_duplicates(groups: &[Vec<CleanupTarget>], mode: LinkMode, confirm_changed: bool, protected: &ProtectedPaths) -> CleanupBatch {
+    let mut entries = Vec::new();
+    let mut failures = Vec::new();
+
+    for group in groups {
+        let Some((source, duplicates)) = group.split_first() else {
+            continue;
+        };
+        let source_sha256 = match verify_source(source, confirm_changed) {
+            Ok(sha256) => sha256,
+            Err(failure) => {
+                log::warn!("Skipping duplicates of {:?}: {}", source.path, failure.error);
+                failures.extend(duplicates.iter().map(|duplicate| CleanupFailure::new(&duplicate.path, format!("Kept copy is unusable: {}", failure.error))));
+                continue;
+            }
+        };
+
+        for duplicate in duplicates {
+            if let Some(reason) = protected.violation(&duplicate.path) {
+                failures.push(CleanupFailure::new(&duplicate.path, format!("Protected path: {}", reason)));
+                continue;
+            }
+            if let Err(failure) = cleanup::verify_target(duplicate, confirm_changed) {
+                failures.push(failure);
+                continue;
+            }
+            match link_duplicate(&source.path, &duplicate.path, &source_sha256, mode) {
+                Ok(info) => {
+                    log::info!("Linked {:?} to {:?} ({:?})", duplicate.path, source.path, info.mode);
+                    entries.push(CleanupJournalEntry {
+                        original_path: duplicate.path.clone(),
+                        size: duplicate.size,
+                        is_dir: false,
+                        trash_location: None,
+                        restored: false,
+                        link: Some(info),
+                    });
+                }
+                Err(error) => {
+                    log::warn!("Failed to link {:?}: {}", duplicate.path, error);
+                    failures.push(CleanupFailure::new(&duplicate.path, error));
+                }
+            }
+        }
+    }
+
+    CleanupBatch {
+        batch_id: format!("link-{}", Utc::now().format("%Y%m%d-%H%M%S-%6f")),
+        created_at: Utc::now(),
+        cleanup_types: vec![LINKED_DUPLICATES.to_string()],
+        permanent: false,
+        freed_size: entries.iter().map(|entry| entry.size).sum(),
+        entries,
+        failures,
+    }
+}
+
+fn verify_source(source: &CleanupTarget, confirm_changed: bool) -> Result<String, CleanupFailure> {
+    cleanup::verify_target(source, confirm_changed)?;
+    sha256_file(&source.path).map_err(|e| CleanupFailure::new(&source.path, e.to_string()))
+}
+
+fn link_duplicate(source: &Path, duplicate: &Path, source_sha256: &str, mode: LinkMode) -> Result<LinkInfo, String> {
+    let duplicate_metadata = fs::symlink_metadata(duplicate).map_err(|e| e.to_string())?;
+    let source_identity = FileIdentity::of_path(source).map_err(|e| e.to_string())?;
+    let duplicate_identity = FileIdentity::from_metadata(&duplicate_metadata);
+    if (source_identity.device, source_identity.inode) == (duplicate_identity.device, duplicate_identity.inode) {
+        return Err("Already a hard link to the kept copy".to_string());
+    }
+    if sha256_file(duplicate).map_err(|e| e.to_string())? != source_sha256 {
+        return Err("Content differs from the kept copy".to_string());
+    }
+
+    let partial = temporary_path(duplicate);
+    let linked = match mode {
+        LinkMode::Hardlink => hard_link(source, &partial, &duplicate_metadata).map(|()| LinkMode::Hardlink),
+        LinkMode::Reflink => reflink(source, &partial, &duplicate_metadata).map(|()| LinkMode::Reflink),
+        LinkMode::Auto => reflink(source, &partial, &duplicate_metadata)
+            .map(|()| LinkMode::Reflink)
+            .or_else(|reflink_error| {
+                let _ = fs::remove_file(&partial);
+                hard_link(source, &partial, &duplicate_metadata)
+                    .map(|()| LinkMode::Hardlink)
+                    .map_err(|error| format!("{}; {}", reflink_error, error))
+            }),
+    };
+    let linked = linked.and_then(|mode| fs::rename(&partial, duplicate).map(|()| mode).map_err(|e| e.to_string()));
+
+    match linked {
+        Ok(mode) => Ok(LinkInfo {
+            source: source.to_path_buf(),
+            mode,
+            sha256: source_sha256.to_string(),
+            permissions: unix_mode(&duplicate_metadata),
+        }),
+        Err(error) => {
+            let _ = fs::remove_file(&partial);
+            Err(error)
+        }
+    }
+}
+
+/// Redonne au chemin lié sa propre copie du contenu, avec les permissions d'origine.
+pub fn unlink_entry(entry: &CleanupJournalEntry) -> Result<(), String> {
+    let Some(info) = &entry.link else {
+        return Err("Entry was not linked".to_string());
+    };
+    let path = &entry.original_path;
+    if sha256_file(path).map_err(|e| e.to_string())? != info.sha256 {
+        return Err("Content changed since it was linked".to_string());
+    }
+
+    let partial = temporary_path(path);
+    let separated = copy_contents(path, &partial)
+        .and_then(|()| match info.permissions {
+            Some(mode) => set_unix_mode(&partial, mode),
+            None => Ok(()),
+        })
+        .and_then(|()| fs::rename(&partial, path));
+    separated.map_err(|e| {
+        let _ = fs::remove_file(&partial);
+        e.to_string()
+    })
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.diskvader-link", name))
+}
+
+fn copy_contents(source: &Path, destination: &Path) -> io::Result<()> {
+    let mut input = File::open(source)?;
+    let mut output = OpenOptions::new().write(true).create_new(true).open(destination)?;
+    io::copy(&mut input, &mut output)?;
+    let modified = input.metadata()?.modified()?;
+    output.set_modified(modified)
+}
+
+fn hard_link(source: &Path, partial: &Path, duplicate: &Metadata) -> Result<(), String> {
+    let source_metadata = fs::metadata(source).map_err(|e| e.to_string())?;
+    if !same_device(&source_metadata, duplicate) {
+        return Err("Hard links need both files on the same device".to_string());
+    }
+    // Un lien physique partage les permissions : il ne doit pas les changer pour ce chemin
+    if unix_mode(&source_metadata) != unix_mode(duplicate) || !same_owner(&source_metadata, duplicate) {
+        return Err("Permissions differ from the kept copy, a hard link would change them".to_string());
+    }
+    fs::hard_link(source, partial).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, partial: &Path, duplicate: &Metadata) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    let input = File::open(source).map_err(|e| e.to_string())?;
+    let output = OpenOptions::new().write(true).create_new(true).open(partial).map_err(|e| e.to_string())?;
+    // SAFETY: les deux descripteurs restent ouverts pendant l'appel
+    if unsafe { libc::ioctl(output.as_raw_fd(), libc::FICLONE, input.as_raw_fd()) } != 0 {
+        return Err(format!("Reflinks are not supported here: {}", io::Error::last_os_error()));
+    }
+    output.set_permissions(fs::Permissions::from_mode(duplicate.permissions().mode())).map_err(|e| e.to_string())?;
+    if let Ok(modified) = duplicate.modified() {
+        output.set_modified(modified).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_source: &Path, _partial: &Path, _duplicate: &Metadata) -> Result<(), String> {
+    Err("Reflinks are not supported on this platform".to_string())
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_unix_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_unix_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn same_device(first: &Metadata, second: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    first.dev() == second.dev()
+}
+
+#[cfg(not(unix))]
+fn same_device(_first: &Metadata, _second: &Metadata) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn same_owner(first: &Metadata, second: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    first.uid() == second.uid() && first.gid() == second.gid()
+}
+
+#[cfg(not(unix))]
+fn same_owner(_first: &Metadata, _second: &Metadata) -> bool {
+    true
+}
+
+/// Cibles d'un lien : la copie conservée puis les doublons, chacun retrouvé dans le scan courant.
+pub fn link_targets(results: &ScanResults, file_ids: &[String]) -> Result<Vec<CleanupTarget>, CommandError> {
+    file_ids.iter()
+        .map(|file_id| {
+            let file = results.find_file(file_id)?;
+            Ok(CleanupTarget {
+                path: file.path.clone(),
+                size: file.size,
+                is_dir: false,
+                identity: file.identity.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("diskvader-dedup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn target(path: &Path) -> CleanupTarget {
+        CleanupTarget {
+            path: path.to_path_buf(),
+            size: fs::metadata(path).unwrap().len(),
+            is_dir: false,
+            identity: Some(FileIdentity::of_path(path).unwrap()),
+        }
+    }
+
+    fn scanned(path: &Path) -> ScannedFile {
+        ScannedFile {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            path: path.to_path_buf(),
+            size: fs::metadata(path).unwrap().len(),
+            file_type: "Other".to_string(),
+            extension: String::new(),
+            identity: Some(FileIdentity::of_path(path).unwrap()),
+        }
+    }
+
+    fn inode(path: &Path) -> u64 {
+        FileIdentity::of_path(path).unwrap().inode
+    }
+
+    #[test]
+    fn duplicate_groups_compare_prefixes_then_full_contents() {
+        let root = temp_dir("groups");
+        let content = vec![3u8; 200_000];
+        let mut same_prefix = content.clone();
+        same_prefix[150_000] = 4;
+        let mut other_prefix = content.clone();
+        other_prefix[0] = 4;
+        fs::write(root.join("a.bin"), &content).unwrap();
+        fs::write(root.join("b.bin"), &content).unwrap();
+        fs::write(root.join("c.bin"), &same_prefix).unwrap();
+        fs::write(root.join("d.bin"), &other_prefix).unwrap();
+        fs::hard_link(root.join("a.bin"), root.join("a-link.bin")).unwrap();
+
+        let results = ScanResults {
+            largest_files: ["a.bin", "a-link.bin", "b.bin", "c.bin", "d.bin"].iter().map(|name| scanned(&root.join(name))).collect(),
+            ..ScanResults::default()
+        };
+        let groups = verified_duplicate_groups(&results);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].size, 200_000);
+        let mut names: Vec<String> = groups[0].files.iter().map(|file| file.path.file_name().unwrap().to_string_lossy().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["a.bin", "b.bin"]);
+        assert_eq!(groups[0].sha256, sha256_file(&root.join("b.bin")).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn hard_link_replaces_the_duplicate_through_a_temporary_file() {
+        let root = temp_dir("link");
+        fs::write(root.join("kept.bin"), b"same content").unwrap();
+        fs::write(root.join("copy.bin"), b"same content").unwrap();
+
+        let batch = link_duplicates(&[vec![target(&root.join("kept.bin")), target(&root.join("copy.bin"))]], LinkMode::Hardlink, false, &ProtectedPaths::default());
+        assert!(batch.failures.is_empty(), "{:?}", batch.failures);
+        assert_eq!(batch.freed_size, 12);
+        let link = batch.entries[0].link.as_ref().unwrap();
+        assert_eq!((link.mode, link.source.clone()), (LinkMode::Hardlink, root.join("kept.bin")));
+
+        assert_eq!(inode(&root.join("copy.bin")), inode(&root.join("kept.bin")));
+        assert_eq!(fs::read(root.join("copy.bin")).unwrap(), b"same content");
+        assert!(!temporary_path(&root.join("copy.bin")).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_link_keeps_the_original_duplicate() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = temp_dir("link-failure");
+        fs::write(root.join("kept.bin"), b"same content").unwrap();
+        fs::write(root.join("copy.bin"), b"same content").unwrap();
+        fs::set_permissions(root.join("copy.bin"), fs::Permissions::from_mode(0o600)).unwrap();
+        fs::set_permissions(root.join("kept.bin"), fs::Permissions::from_mode(0o644)).unwrap();
+        let before = inode(&root.join("copy.bin"));
+
+        // Un lien physique changerait les permissions du doublon : refusé
+        let batch = link_duplicates(&[vec![target(&root.join("kept.bin")), target(&root.join("copy.bin"))]], LinkMode::Hardlink, false, &ProtectedPaths::default());
+        assert!(batch.entries.is_empty());
+        assert_eq!(batch.failures.len(), 1);
+        assert_eq!(inode(&root.join("copy.bin")), before);
+        assert_eq!(fs::read(root.join("copy.bin")).unwrap(), b"same content");
+        assert!(!temporary_path(&root.join("copy.bin")).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn changed_content_is_not_linked() {
+        let root = temp_dir("link-changed");
+        fs::write(root.join("kept.bin"), b"same content").unwrap();
+        fs::write(root.join("copy.bin"), b"other bytes!").unwrap();
+
+        let batch = link_duplicates(&[vec![target(&root.join("kept.bin")), target(&root.join("copy.bin"))]], LinkMode::Hardlink, false, &ProtectedPaths::default());
+        assert!(batch.entries.is_empty());
+        assert_eq!(fs::read(root.join("copy.bin")).unwrap(), b"other bytes!");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unlink_gives_the_path_its_own_copy_back() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = temp_dir("unlink");
+        fs::write(root.join("kept.bin"), b"same content").unwrap();
+        fs::write(root.join("copy.bin"), b"same content").unwrap();
+        let batch = link_duplicates(&[vec![target(&root.join("kept.bin")), target(&root.join("copy.bin"))]], LinkMode::Hardlink, false, &ProtectedPaths::default());
+        let entry = &batch.entries[0];
+
+        unlink_entry(entry).unwrap();
+        assert_ne!(inode(&root.join("copy.bin")), inode(&root.join("kept.bin")));
+        assert_eq!(fs::read(root.join("copy.bin")).unwrap(), b"same content");
+        let mode = fs::metadata(root.join("copy.bin")).unwrap().permissions().mode();
+        assert_eq!(Some(mode), entry.link.as_ref().unwrap().permissions);
+        assert!(!temporary_path(&root.join("copy.bin")).exists());
+
+        // Contenu modifié depuis le lien : la copie n'est pas recréée
+        fs::write(root.join("copy.bin"), b"edited later").unwrap();
+        assert!(unlink_entry(entry).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::models::{CommandError, ScanResults};
+
+// Bornes supérieures des tranches d'âge, en jours ; au-delà, la dernière tranche
+const AGE_BUCKET_DAYS: [u64; 7] = [7, 30, 90, 180, 365, 730, 1825];
+
+pub const AGE_BUCKET_LABELS: [&str; 9] = [
+    "< 1 week", "< 1 month", "< 3 months", "< 6 months", "< 1 year",
+    "< 2 years", "< 5 years", "> 5 years", "Unknown",
+];
+
+// Bornes supérieures exclusives des tranches de taille, en octets : progression logarithmique
+const SIZE_BUCKET_LIMITS: [u64; 8] = [
+    1, 4 << 10, 64 << 10, 1 << 20, 16 << 20, 256 << 20, 1 << 30, 10 << 30,
+];
+
+pub const SIZE_BUCKET_LABELS: [&str; 9] = [
+    "0 B", "< 4 KB", "< 64 KB", "< 1 MB", "< 16 MB",
+    "< 256 MB", "< 1 GB", "< 10 GB", ">= 10 GB",
+];
+
+/// Nombre de fichiers et octets par tranche.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Histogram {
+    pub counts: Vec<u64>,
+    pub sizes: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn add(&mut self, bucket: usize, size: u64) {
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+            self.sizes.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+        self.sizes[bucket] += size;
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+            self.sizes.resize(other.sizes.len(), 0);
+        }
+        for (bucket, (count, size)) in other.counts.iter().zip(&other.sizes).enumerate() {
+            self.counts[bucket] += count;
+            self.sizes[bucket] += size;
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.sizes.iter().sum()
+    }
+
+    /// Tranches nommées, y compris les tranches vides.
+    pub fn buckets(&self, labels: &[&str]) -> Vec<HistogramBucket> {
+        labels.iter()
+            .enumerate()
+            .map(|(bucket, label)| HistogramBucket {
+                label: label.to_string(),
+                count: self.counts.get(bucket).copied().unwrap_or(0),
+                size: self.sizes.get(bucket).copied().unwrap_or(0),
+            })
+            .collect()
+    }
+}
+
+/// Histogrammes des fichiers d'un dossier pour une catégorie, âges calculés à l'instant du scan.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FileHistograms {
+    pub modified: Histogram,
+    pub accessed: Histogram,
+    #[serde(default)]
+    pub sizes: Histogram,
+}
+
+impl FileHistograms {
+    pub fn record(&mut self, metadata: &Metadata, reference: SystemTime) {
+        self.record_dated(metadata.len(), metadata.modified().ok(), metadata.accessed().ok(), reference);
+    }
+
+    /// Taille et dates connues, une date absente allant dans la tranche "Unknown".
+    pub fn record_dated(&mut self, size: u64, modified: Option<SystemTime>, accessed: Option<SystemTime>, reference: SystemTime) {
+        self.modified.add(age_bucket(modified, reference), size);
+        self.accessed.add(age_bucket(accessed, reference), size);
+        self.record_size(size);
+    }
+
+    /// Taille seule, pour les imports qui ne connaissent pas les dates.
+    pub fn record_size(&mut self, size: u64) {
+        self.sizes.add(size_bucket(size), size);
+    }
+
+    pub fn merge(&mut self, other: &FileHistograms) {
+        self.modified.merge(&other.modified);
+        self.accessed.merge(&other.accessed);
+        self.sizes.merge(&other.sizes);
+    }
+}
+
+pub fn size_bucket(size: u64) -> usize {
+    SIZE_BUCKET_LIMITS.iter()
+        .position(|limit| size < *limit)
+        .unwrap_or(SIZE_BUCKET_LIMITS.len())
+}
+
+/// Tranche d'âge d'une date ; une date dans le futur compte comme récente.
+pub fn age_bucket(time: Option<SystemTime>, reference: SystemTime) -> usize {
+    let Some(time) = time else {
+        return AGE_BUCKET_LABELS.len() - 1;
+    };
+    let days = reference.duration_since(time).map_or(0, |age| age.as_secs() / 86_400);
+    AGE_BUCKET_DAYS.iter()
+        .position(|limit| days < *limit)
+        .unwrap_or(AGE_BUCKET_DAYS.len())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub label: String,
+    pub count: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeHistograms {
+    pub size: u64,
+    pub modified: Vec<HistogramBucket>,
+    pub accessed: Vec<HistogramBucket>,
+}
+
+impl From<&FileHistograms> for AgeHistograms {
+    fn from(histograms: &FileHistograms) -> Self {
+        AgeHistograms {
+            size: histograms.modified.total_size(),
+            modified: histograms.modified.buckets(&AGE_BUCKET_LABELS),
+            accessed: histograms.accessed.buckets(&AGE_BUCKET_LABELS),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FolderAges {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub histograms: AgeHistograms,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CategoryAges {
+    pub category: String,
+    #[serde(flatten)]
+    pub histograms: AgeHistograms,
+}
+
+/// Âge des données d'un dossier : au total, par sous-dossier direct et par catégorie de fichier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeReport {
+    pub path: PathBuf,
+    /// Instant du scan, à partir duquel les âges sont comptés
+    pub reference: Option<DateTime<Utc>>,
+    pub total: AgeHistograms,
+    pub folders: Vec<FolderAges>,
+    pub categories: Vec<CategoryAges>,
+}
+
+/// Répartition des fichiers d'un dossier par tranche de taille.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SizeReport {
+    pub path: PathBuf,
+    pub file_count: u64,
+    pub size: u64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+// Dossier analysé : la racine du scan par défaut, jamais un dossier extérieur
+fn report_path(results: &ScanResults, folder: Option<&Path>) -> Result<PathBuf, CommandError> {
+    let path = folder.map_or_else(|| PathBuf::from(&results.scan_path), Path::to_path_buf);
+    if !path.starts_with(&results.scan_path) {
+        return Err(CommandError::InternalError(format!("{} is outside the scanned folder", path.display())));
+    }
+    Ok(path)
+}
+
+// Les instantanés de la version 1 ne conservent pas les histogrammes : mieux vaut le dire que tout afficher à zéro
+fn require_histograms(results: &ScanResults) -> Result<(), CommandError> {
+    if results.histograms.is_empty() && results.total_files > 0 {
+        return Err(CommandError::InternalError("No histograms were recorded for this scan, rescan the folder to compute them".to_string()));
+    }
+    Ok(())
+}
+
+/// Histogrammes d'âge de `folder` (la racine du scan par défaut), sous-dossiers compris.
+pub fn age_report(results: &ScanResults, folder: Option<&Path>) -> Result<AgeReport, CommandError> {
+    require_histograms(results)?;
+    let path = report_path(results, folder)?;
+
+    let mut total = FileHistograms::default();
+    let mut folders: HashMap<PathBuf, FileHistograms> = HashMap::new();
+    let mut categories: HashMap<&str, FileHistograms> = HashMap::new();
+    for (folder_path, by_category) in &results.histograms {
+        let Ok(relative) = folder_path.strip_prefix(&path) else {
+            continue;
+        };
+        // Sous-dossier direct de `path` qui contient ce dossier, aucun pour ses propres fichiers
+        let child = match relative.components().next() {
+            Some(Component::Normal(name)) => Some(path.join(name)),
+            _ => None,
+        };
+        for (category, histograms) in by_category {
+            total.merge(histograms);
+            categories.entry(category.as_str()).or_default().merge(histograms);
+            if let Some(child) = &child {
+                folders.entry(child.clone()).or_default().merge(histograms);
+            }
+        }
+    }
+
+    let mut folders: Vec<FolderAges> = folders.into_iter()
+        .map(|(path, histograms)| FolderAges {
+            name: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+            histograms: AgeHistograms::from(&histograms),
+            path,
+        })
+        .collect();
+    folders.sort_by_key(|folder| Reverse(folder.histograms.size));
+
+    let mut categories: Vec<CategoryAges> = categories.into_iter()
+        .map(|(category, histograms)| CategoryAges {
+            category: category.to_string(),
+            histograms: AgeHistograms::from(&histograms),
+        })
+        .collect();
+    categories.sort_by_key(|category| Reverse(category.histograms.size));
+
+    Ok(AgeReport {
+        path,
+        reference: results.scanned_at,
+        total: AgeHistograms::from(&total),
+        folders,
+        categories,
+    })
+}
+
+/// Histogramme des tailles de `folder` (la racine du scan par défaut), sous-dossiers compris.
+pub fn size_report(results: &ScanResults, folder: Option<&Path>) -> Result<SizeReport, CommandError> {
+    require_histograms(results)?;
+    let path = report_path(results, folder)?;
+    let mut sizes = Histogram::default();
+    for (_, by_category) in results.histograms.iter().filter(|(folder_path, _)| folder_path.starts_with(&path)) {
+        for histograms in by_category.values() {
+            sizes.merge(&histograms.sizes);
+        }
+    }
+
+    Ok(SizeReport {
+        file_count: sizes.counts.iter().sum(),
+        size: sizes.total_size(),
+        buckets: sizes.buckets(&SIZE_BUCKET_LABELS),
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const DAY: u64 = 86_400;
+
+    fn days_before(reference: SystemTime, days: u64) -> Option<SystemTime> {
+        reference.checked_sub(Duration::from_secs(days * DAY))
+    }
+
+    fn histograms(bucket: usize, size: u64) -> FileHistograms {
+        let mut histograms = FileHistograms::default();
+        histograms.modified.add(bucket, size);
+        histograms.accessed.add(AGE_BUCKET_LABELS.len() - 1, size);
+        histograms.record_size(size);
+        histograms
+    }
+
+    #[test]
+    fn age_bucket_edges() {
+        let reference = SystemTime::now();
+        assert_eq!(age_bucket(days_before(reference, 0), reference), 0);
+        assert_eq!(age_bucket(days_before(reference, 6), reference), 0);
+        assert_eq!(age_bucket(days_before(reference, 7), reference), 1);
+        assert_eq!(age_bucket(days_before(reference, 364), reference), 4);
+        assert_eq!(age_bucket(days_before(reference, 365), reference), 5);
+        assert_eq!(age_bucket(days_before(reference, 1824), reference), 6);
+        assert_eq!(age_bucket(days_before(reference, 1825), reference), 7);
+        assert_eq!(age_bucket(days_before(reference, 20_000), reference), 7);
+    }
+
+    #[test]
+    fn age_bucket_handles_future_and_missing_dates() {
+        let reference = SystemTime::now();
+        // Horloge décalée ou fichier daté dans le futur : compté comme récent
+        assert_eq!(age_bucket(reference.checked_add(Duration::from_secs(30 * DAY)), reference), 0);
+        // atime indisponible (montage noatime, listing sans date)
+        assert_eq!(age_bucket(None, reference), AGE_BUCKET_LABELS.len() - 1);
+        assert_eq!(AGE_BUCKET_LABELS[age_bucket(None, reference)], "Unknown");
+    }
+
+    #[test]
+    fn record_dated_counts_missing_access_time_as_unknown() {
+        let reference = SystemTime::now();
+        let mut histograms = FileHistograms::default();
+        histograms.record_dated(4 << 10, days_before(reference, 100), None, reference);
+
+        assert_eq!(histograms.modified.counts, [0, 0, 0, 1]);
+        assert_eq!(histograms.accessed.counts[AGE_BUCKET_LABELS.len() - 1], 1);
+        assert_eq!(histograms.sizes.counts, [0, 0, 1]);
+    }
+
+    #[test]
+    fn reports_require_recorded_histograms() {
+        // Scan antérieur aux histogrammes : des fichiers mais aucune tranche
+        let results = ScanResults { scan_path: "/data".to_string(), total_files: 3, ..ScanResults::default() };
+        assert!(age_report(&results, None).is_err());
+        assert!(size_report(&results, None).is_err());
+
+        let empty = ScanResults { scan_path: "/data".to_string(), ..ScanResults::default() };
+        assert_eq!(age_report(&empty, None).unwrap().total.size, 0);
+    }
+
+    #[test]
+    fn histogram_merge_grows_to_the_longest() {
+        let mut first = Histogram::default();
+        first.add(1, 10);
+        let mut second = Histogram::default();
+        second.add(3, 5);
+        second.add(1, 2);
+
+        first.merge(&second);
+        assert_eq!(first.counts, [0, 2, 0, 1]);
+        assert_eq!(first.sizes, [0, 12, 0, 5]);
+        assert_eq!(first.total_size(), 17);
+        assert_eq!(first.buckets(&AGE_BUCKET_LABELS).len(), AGE_BUCKET_LABELS.len());
+    }
+
+    #[test]
+    fn age_report_groups_by_child_folder_and_category() {
+        let mut results = ScanResults { scan_path: "/data".to_string(), ..ScanResults::default() };
+        results.histograms.insert(PathBuf::from("/data"), HashMap::from([("Documents".to_string(), histograms(0, 10))]));
+        results.histograms.insert(PathBuf::from("/data/photos/2020"), HashMap::from([("Images".to_string(), histograms(7, 300))]));
+        results.histograms.insert(PathBuf::from("/data/photos"), HashMap::from([("Images".to_string(), histograms(2, 100))]));
+        results.histograms.insert(PathBuf::from("/data/music"), HashMap::from([("Audio".to_string(), histograms(4, 50))]));
+
+        let report = age_report(&results, None).unwrap();
+        assert_eq!(report.total.size, 460);
+        assert_eq!(report.total.modified.iter().map(|bucket| bucket.count).sum::<u64>(), 4);
+        assert_eq!(report.total.accessed[8].count, 4);
+
+        let folders: Vec<(&str, u64)> = report.folders.iter().map(|folder| (folder.name.as_str(), folder.histograms.size)).collect();
+        assert_eq!(folders, [("photos", 400), ("music", 50)]);
+        let categories: Vec<(&str, u64)> = report.categories.iter().map(|category| (category.category.as_str(), category.histograms.size)).collect();
+        assert_eq!(categories, [("Images", 400), ("Audio", 50), ("Documents", 10)]);
+
+        let photos = age_report(&results, Some(Path::new("/data/photos"))).unwrap();
+        assert_eq!(photos.total.size, 400);
+        assert_eq!(photos.folders.len(), 1);
+        assert!(age_report(&results, Some(Path::new("/elsewhere"))).is_err());
+    }
+
+    #[test]
+    fn size_bucket_edges() {
+        assert_eq!(size_bucket(0), 0);
+        assert_eq!(SIZE_BUCKET_LABELS[size_bucket(0)], "0 B");
+        assert_eq!(size_bucket(1), 1);
+        assert_eq!(size_bucket(4095), 1);
+        // Exactement 4 KiB : tranche suivante
+        assert_eq!(size_bucket(4 << 10), 2);
+        assert_eq!(size_bucket((10 << 30) - 1), 7);
+        assert_eq!(size_bucket(10 << 30), 8);
+        assert_eq!(size_bucket(u64::MAX), SIZE_BUCKET_LABELS.len() - 1);
+    }
+
+    #[test]
+    fn size_report_counts_files_below_the_folder() {
+        let mut results = ScanResults { scan_path: "/data".to_string(), ..ScanResults::default() };
+        results.histograms.insert(PathBuf::from("/data"), HashMap::from([("Documents".to_string(), histograms(0, 0))]));
+        results.histograms.insert(PathBuf::from("/data/videos"), HashMap::from([("Videos".to_string(), histograms(0, 20 << 30))]));
+        results.histograms.insert(PathBuf::from("/data/videos/clips"), HashMap::from([("Videos".to_string(), histograms(0, 4 << 10))]));
+
+        let report = size_report(&results, None).unwrap();
+        assert_eq!((report.file_count, report.size), (3, (20 << 30) + (4 << 10)));
+        let counts: Vec<u64> = report.buckets.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, [1, 0, 1, 0, 0, 0, 0, 0, 1]);
+
+        let videos = size_report(&results, Some(Path::new("/data/videos"))).unwrap();
+        assert_eq!(videos.file_count, 2);
+        assert!(size_report(&results, Some(Path::new("/elsewhere"))).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+pub mod accounting;
+pub mod analyzers;
+pub mod archive;
+pub mod artifacts;
+mod builder;
+pub mod caches;
+pub mod cleanup;
+pub mod dedup;
+pub mod histograms;
+pub mod listing;
+pub mod models;
+pub mod ncdu;
+pub mod offload;
+pub mod openfiles;
+pub mod operation;
+pub mod protected;
+pub mod report;
+pub mod scanner;
+pub mod snapshot;
+pub mod thumbnails;
+pub mod trashbin;
+pub mod volumes;
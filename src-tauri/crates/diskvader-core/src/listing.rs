@@ -0,0 +1,384 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::builder::ResultsBuilder;
+use crate::models::{CommandError, ScanResults};
+
+/// Formats de listing acceptés pour l'analyse hors ligne d'une autre machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingFormat {
+    /// `find <dir> -printf '%s %T@ %p\n'`
+    Find,
+    /// `du -ab <dir>`
+    Du,
+    /// `ls -lR <dir>`
+    Ls,
+}
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+fn invalid(line_number: usize, message: &str) -> CommandError {
+    CommandError::InternalError(format!("Invalid listing at line {}: {}", line_number, message))
+}
+
+pub fn import_listing_file(path: &Path, format: Option<ListingFormat>) -> Result<ScanResults, CommandError> {
+    if !path.exists() {
+        return Err(CommandError::PathDoesNotExist(path.to_string_lossy().to_string()));
+    }
+    let bytes = fs::read(path)?;
+    import_listing_str(&String::from_utf8_lossy(&bytes), format)
+}
+
+pub fn import_listing_str(content: &str, format: Option<ListingFormat>) -> Result<ScanResults, CommandError> {
+    let format = match format {
+        Some(format) => format,
+        None => detect_format(content)
+            .ok_or_else(|| CommandError::InternalError("Unrecognized listing format".to_string()))?,
+    };
+
+    match format {
+        ListingFormat::Find => import_sized_paths(parse_find(content)?),
+        ListingFormat::Du => import_sized_paths(parse_du(content)?),
+        ListingFormat::Ls => import_ls(content),
+    }
+}
+
+pub fn detect_format(content: &str) -> Option<ListingFormat> {
+    for line in content.lines().map(str::trim_end).filter(|l| !l.is_empty()).take(20) {
+        if is_ls_total_line(line) || parse_ls_entry(line).is_some() {
+            return Some(ListingFormat::Ls);
+        }
+
+        if let Some((size, rest)) = line.split_once('\t') {
+            if size.parse::<u64>().is_ok() && !rest.is_empty() {
+                return Some(ListingFormat::Du);
+            }
+        }
+
+        let mut fields = line.splitn(3, ' ');
+        if let (Some(size), Some(mtime), Some(_)) = (fields.next(), fields.next(), fields.next()) {
+            if size.parse::<u64>().is_ok() && mtime.parse::<f64>().is_ok() {
+                return Some(ListingFormat::Find);
+            }
+        }
+    }
+    None
+}
+
+fn parse_find(content: &str) -> Result<Vec<(PathBuf, u64, Option<SystemTime>)>, CommandError> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.splitn(3, ' ');
+        let size = fields.next()
+            .and_then(|size| size.parse::<u64>().ok())
+            .ok_or_else(|| invalid(index + 1, "expected '<size> <mtime> <path>'"))?;
+        let mtime = fields.next()
+            .and_then(|mtime| mtime.parse::<f64>().ok())
+            .ok_or_else(|| invalid(index + 1, "invalid modification time"))?;
+        let path = fields.next()
+            .ok_or_else(|| invalid(index + 1, "missing path"))?;
+        entries.push((PathBuf::from(path), size, unix_time(mtime)));
+    }
+    Ok(entries)
+}
+
+fn parse_du(content: &str) -> Result<Vec<(PathBuf, u64, Option<SystemTime>)>, CommandError> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (size, path) = line.split_once('\t')
+            .ok_or_else(|| invalid(index + 1, "expected '<size>\\t<path>'"))?;
+        let size = size.trim().parse::<u64>()
+            .map_err(|_| invalid(index + 1, "invalid size, use du -ab for sizes in bytes"))?;
+        entries.push((PathBuf::from(path), size, None));
+    }
+    Ok(entries)
+}
+
+// Secondes depuis l'époque Unix telles qu'affichées par %T@, éventuellement négatives
+fn unix_time(seconds: f64) -> Option<SystemTime> {
+    let offset = Duration::try_from_secs_f64(seconds.abs()).ok()?;
+    if seconds >= 0.0 {
+        UNIX_EPOCH.checked_add(offset)
+    } else {
+        UNIX_EPOCH.checked_sub(offset)
+    }
+}
+
+// find et du ne disent pas si une entrée est un dossier : tout chemin parent d'une autre entrée en est un,
+// et les tailles des dossiers sont recalculées à partir des fichiers
+fn import_sized_paths(entries: Vec<(PathBuf, u64, Option<SystemTime>)>) -> Result<ScanResults, CommandError> {
+    if entries.is_empty() {
+        return Err(CommandError::InternalError("Listing contains no entries".to_string()));
+    }
+
+    let mut directories: HashSet<&Path> = HashSet::new();
+    for (path, _, _) in &entries {
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if dir.as_os_str().is_empty() || !directories.insert(dir) {
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+
+    let root = common_root(entries.iter().map(|(path, _, _)| path.as_path()), &directories);
+
+    let mut builder = ResultsBuilder::new();
+    for (path, size, modified) in &entries {
+        if directories.contains(path.as_path()) {
+            builder.add_directory(path);
+        } else {
+            builder.add_dated_file(path.clone(), *size, *modified);
+        }
+    }
+
+    Ok(builder.finish(&root))
+}
+
+fn common_root<'a>(paths: impl Iterator<Item = &'a Path>, directories: &HashSet<&Path>) -> PathBuf {
+    let mut root: Option<PathBuf> = None;
+    for path in paths {
+        let candidate = if directories.contains(path) {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        root = Some(match root {
+            None => candidate.to_path_buf(),
+            Some(current) => current.ancestors()
+                .find(|ancestor| candidate.starts_with(ancestor))
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        });
+    }
+    root.unwrap_or_default()
+}
+
+fn is_ls_total_line(line: &str) -> bool {
+    line.strip_prefix("total ")
+        .map(|rest| !rest.trim().is_empty() && rest.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == ','))
+        .unwrap_or(false)
+}
+
+fn is_ls_date(token: &str) -> bool {
+    let lower = token.trim_end_matches('.').to_lowercase();
+    MONTHS.contains(&lower.as_str())
+        || (token.len() == 10 && token.is_ascii() && token.as_bytes()[4] == b'-' && token.as_bytes()[7] == b'-' && token[..4].parse::<u16>().is_ok())
+}
+
+struct LsEntry<'a> {
+    kind: char,
+    size: Option<u64>,
+    name: &'a str,
+}
+
+// Ligne d'entrée de `ls -l` : droits, liens, propriétaire, [groupe], taille, date (2 ou 3 champs), nom
+fn parse_ls_entry(line: &str) -> Option<LsEntry<'_>> {
+    let permissions = line.split_whitespace().next()?;
+    let kind = permissions.chars().next()?;
+    if permissions.len() < 10 || !permissions.is_ascii() || !"-dlcbps".contains(kind)
+        || !permissions[1..10].chars().all(|c| "rwxsStTl-".contains(c)) {
+        return None;
+    }
+
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (offset, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(offset),
+            (true, Some(begin)) => {
+                tokens.push((begin, &line[begin..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push((begin, &line[begin..]));
+    }
+
+    let date_index = (3..tokens.len())
+        .find(|&i| is_ls_date(tokens[i].1) && tokens[i - 1].1.parse::<u64>().is_ok())?;
+    let date_fields = if tokens[date_index].1.contains('-') {
+        // --time-style=long-iso ou full-iso (avec fuseau horaire)
+        match tokens.get(date_index + 2) {
+            Some((_, zone)) if zone.starts_with('+') || zone.starts_with('-') => 3,
+            _ => 2,
+        }
+    } else {
+        3
+    };
+    let (name_offset, _) = *tokens.get(date_index + date_fields)?;
+
+    Some(LsEntry {
+        kind,
+        size: tokens[date_index - 1].1.parse().ok(),
+        name: &line[name_offset..],
+    })
+}
+
+fn import_ls(content: &str) -> Result<ScanResults, CommandError> {
+    let mut builder = ResultsBuilder::new();
+    let mut root: Option<PathBuf> = None;
+    let mut current_dir = PathBuf::from(".");
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || is_ls_total_line(line) {
+            continue;
+        }
+
+        if let Some(entry) = parse_ls_entry(line) {
+            match entry.kind {
+                '-' => {
+                    let size = entry.size.ok_or_else(|| invalid(index + 1, "invalid file size"))?;
+                    builder.add_file(current_dir.join(entry.name), size);
+                }
+                'd' if entry.name != "." && entry.name != ".." => {
+                    builder.add_directory(&current_dir.join(entry.name));
+                }
+                _ => {} // Liens symboliques, périphériques, sockets
+            }
+            continue;
+        }
+
+        if let Some(dir) = line.strip_suffix(':') {
+            current_dir = PathBuf::from(dir);
+            builder.add_directory(&current_dir);
+            if root.is_none() {
+                root = Some(current_dir.clone());
+            }
+            continue;
+        }
+
+        // Messages d'erreur de ls mêlés au listing (permissions refusées, etc.)
+        log::warn!("Skipping unrecognized ls line {}: {}", index + 1, line);
+    }
+
+    Ok(builder.finish(&root.unwrap_or_else(|| PathBuf::from("."))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder_size(results: &ScanResults, path: &str) -> u64 {
+        results.all_folders.iter()
+            .find(|folder| folder.path == Path::new(path))
+            .map_or(0, |folder| folder.size)
+    }
+
+    #[test]
+    fn detect_format_recognizes_each_listing() {
+        assert_eq!(detect_format("4096 1700000000.5 /data\n12 1700000000.0 /data/a.txt\n"), Some(ListingFormat::Find));
+        assert_eq!(detect_format("12\t/data/a.txt\n4108\t/data\n"), Some(ListingFormat::Du));
+        assert_eq!(detect_format("/data:\ntotal 8\n-rw-r--r-- 1 me me 12 Jan  2 10:00 a.txt\n"), Some(ListingFormat::Ls));
+        assert_eq!(detect_format("hello world\n"), None);
+        assert_eq!(detect_format(""), None);
+    }
+
+    #[test]
+    fn parse_ls_entry_reads_size_and_name() {
+        let entry = parse_ls_entry("-rw-r--r-- 1 me staff 1234 Jan  2 10:00 my file.txt").unwrap();
+        assert_eq!((entry.kind, entry.size, entry.name), ('-', Some(1234), "my file.txt"));
+
+        let entry = parse_ls_entry("drwxr-xr-x  2 me me 4096 Mar 15  2021 old").unwrap();
+        assert_eq!((entry.kind, entry.size, entry.name), ('d', Some(4096), "old"));
+
+        // Sans groupe (ls -lG) et avec --time-style=long-iso
+        let entry = parse_ls_entry("-rw------- 1 me 42 2024-05-01 12:30 notes").unwrap();
+        assert_eq!((entry.size, entry.name), (Some(42), "notes"));
+
+        let entry = parse_ls_entry("-rw-r--r-- 1 me me 7 2024-05-01 12:30:00.000000000 +0200 full.iso").unwrap();
+        assert_eq!((entry.size, entry.name), (Some(7), "full.iso"));
+
+        let entry = parse_ls_entry("lrwxrwxrwx 1 me me 11 Jan  2 10:00 link -> target").unwrap();
+        assert_eq!((entry.kind, entry.name), ('l', "link -> target"));
+    }
+
+    #[test]
+    fn parse_ls_entry_rejects_other_lines() {
+        assert!(parse_ls_entry("total 12").is_none());
+        assert!(parse_ls_entry("/data/sub:").is_none());
+        assert!(parse_ls_entry("ls: cannot open directory '/root': Permission denied").is_none());
+        assert!(parse_ls_entry("-rw-r--r-- 1 me me Jan 2 10:00").is_none());
+    }
+
+    #[test]
+    fn parse_find_reads_size_time_and_path() {
+        let entries = parse_find("12 86400.5 /data/a file.txt\n\n0 -1 /data/old\n").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].0.as_path(), entries[0].1), (Path::new("/data/a file.txt"), 12));
+        assert_eq!(entries[0].2, UNIX_EPOCH.checked_add(Duration::from_secs_f64(86400.5)));
+        assert_eq!(entries[1].2, UNIX_EPOCH.checked_sub(Duration::from_secs(1)));
+
+        assert!(parse_find("abc 1.0 /data/a\n").is_err());
+        assert!(parse_find("12 yesterday /data/a\n").is_err());
+        assert!(parse_find("12 1.0\n").is_err());
+    }
+
+    #[test]
+    fn parse_du_reads_sizes() {
+        let entries = parse_du("12\t/data/a.txt\n4108\t/data\n").unwrap();
+        assert_eq!(entries, vec![
+            (PathBuf::from("/data/a.txt"), 12, None),
+            (PathBuf::from("/data"), 4108, None),
+        ]);
+        assert!(parse_du("12K\t/data/a.txt\n").is_err());
+        assert!(parse_du("12 /data/a.txt\n").is_err());
+    }
+
+    #[test]
+    fn import_find_rebuilds_folders_and_ages() {
+        let content = "4096 1700000000 /data\n4096 1700000000 /data/sub\n100 1700000000 /data/a.txt\n250000 1600000000 /data/sub/b.bin\n";
+        let results = import_listing_str(content, None).unwrap();
+
+        assert_eq!(results.scan_path, "/data");
+        assert_eq!((results.total_files, results.total_size), (2, 250_100));
+        assert_eq!(folder_size(&results, "/data"), 250_100);
+        assert_eq!(folder_size(&results, "/data/sub"), 250_000);
+        assert!(results.scanned_at.is_some());
+        let ages = crate::histograms::age_report(&results, None).unwrap();
+        assert_eq!(ages.total.modified.iter().map(|bucket| bucket.count).sum::<u64>(), 2);
+    }
+
+    #[test]
+    fn import_du_ignores_directory_totals() {
+        let results = import_listing_str("100\t/data/a.txt\n300\t/data/sub/b.txt\n4496\t/data/sub\n8692\t/data\n", None).unwrap();
+
+        assert_eq!(results.scan_path, "/data");
+        assert_eq!((results.total_files, results.total_size), (2, 400));
+        assert_eq!(folder_size(&results, "/data/sub"), 300);
+        assert!(results.scanned_at.is_none());
+    }
+
+    #[test]
+    fn import_ls_follows_directory_headers() {
+        let content = "\
+/data:
+total 12
+drwxr-xr-x 2 me me 4096 Jan  2 10:00 sub
+-rw-r--r-- 1 me me  100 Jan  2 10:00 a.txt
+
+/data/sub:
+total 4
+-rw-r--r-- 1 me me  300 Jan  2 10:00 b c.txt
+";
+        let results = import_listing_str(content, None).unwrap();
+
+        assert_eq!(results.scan_path, "/data");
+        assert_eq!((results.total_files, results.total_size), (2, 400));
+        assert_eq!(folder_size(&results, "/data/sub"), 300);
+    }
+}
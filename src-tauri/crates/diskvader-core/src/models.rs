@@ -0,0 +1,552 @@
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering, AtomicBool};
+use chrono::{DateTime, Utc};
+
+use crate::accounting::AccountingSummary;
+use crate::histograms::FileHistograms;
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum CommandError {
+    #[error("Path does not exist: {0}")]
+    PathDoesNotExist(String),
+    #[error("Path is not a directory: {0}")]
+    PathIsNotDirectory(String),
+    #[error("Cannot read directory: {0}")]
+    CannotReadDirectory(String),
+    #[error("Cannot get metadata: {0}")]
+    CannotGetMetadata(String),
+    #[error("Scan failed: {0}")]
+    ScanFailed(String),
+    #[error("Unknown or stale ID: {0}")]
+    StaleId(String),
+    #[error("Confirmation required: {0}")]
+    ConfirmationRequired(String),
+    #[error("Protected path: {0}")]
+    ProtectedPath(String),
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    #[error("Internal error: {0}")]
+    InternalError(String),
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError::InternalError(err.to_string())
+    }
+}
+
+impl From<std::sync::mpsc::RecvError> for CommandError {
+    fn from(err: std::sync::mpsc::RecvError) -> Self {
+        CommandError::InternalError(err.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for CommandError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        CommandError::InternalError(err.to_string())
+    }
+}
+
+impl From<std::sync::PoisonError<std::sync::MutexGuard<'_, ScanResults>>> for CommandError {
+    fn from(err: std::sync::PoisonError<std::sync::MutexGuard<'_, ScanResults>>) -> Self {
+        CommandError::InternalError(err.to_string())
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ScanProgress {
+    #[serde(rename = "filesAnalyzed")]
+    pub files_analyzed: u32,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    #[serde(rename = "foldersAnalyzed")]
+    pub folders_analyzed: u32,
+    #[serde(rename = "currentPath")]
+    pub current_path: String,
+    #[serde(rename = "progressPercentage")]
+    pub progress_percentage: f64,
+    #[serde(rename = "estimatedTotalSize")]
+    pub estimated_total_size: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScanData {
+    pub total_files: u32,
+    pub total_folders: u32,
+    pub total_size: u64,
+    pub free_space: u64,
+    pub used_percentage: f32,
+    pub scan_time: f32,
+    pub scan_path: String,
+    /// Dernier bilan du volume, absent tant qu'il n'a pas été calculé pour ce scan
+    #[serde(default)]
+    pub accounting: Option<AccountingSummary>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FileItem {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub extension: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FolderItem {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub file_count: u32,
+    #[serde(default)]
+    pub entry_count: u32,
+    pub percentage: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FileTypeDistributionItem {
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub size: u64,
+    pub count: u32,
+    pub color: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PieChartDataItem {
+    pub name: String,
+    pub value: f32,
+    pub color: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GrowthDataItem {
+    pub name: String,
+    pub value: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupSuggestionItem {
+    #[serde(rename = "type")]
+    pub cleanup_type: String,
+    pub size: u64,
+    pub count: u32,
+    pub color_class: String,
+}
+
+/// Fichier ou dossier précis visé par une suggestion de nettoyage.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupCandidate {
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+    pub reason: String,
+    #[serde(default)]
+    pub identity: Option<FileIdentity>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupPreviewGroup {
+    #[serde(rename = "type")]
+    pub cleanup_type: String,
+    pub size: u64,
+    pub count: u32,
+    pub color_class: String,
+    pub items: Vec<CleanupCandidate>,
+}
+
+impl CleanupPreviewGroup {
+    pub fn new(cleanup_type: &str, color_class: &str, items: Vec<CleanupCandidate>) -> Self {
+        CleanupPreviewGroup {
+            cleanup_type: cleanup_type.to_string(),
+            size: items.iter().map(|item| item.size).sum(),
+            count: items.len() as u32,
+            color_class: color_class.to_string(),
+            items,
+        }
+    }
+
+    pub fn summary(&self) -> CleanupSuggestionItem {
+        CleanupSuggestionItem {
+            cleanup_type: self.cleanup_type.clone(),
+            size: self.size,
+            count: self.count,
+            color_class: self.color_class.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrashInfo {
+    pub size: u64,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScannedFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub file_type: String,
+    pub extension: String,
+    #[serde(default)]
+    pub identity: Option<FileIdentity>,
+}
+
+/// Identité d'un fichier relevée au scan, comparée avant toute opération destructive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIdentity {
+    pub device: u64,
+    pub inode: u64,
+    pub size: u64,
+    /// Date de modification en nanosecondes depuis l'époque Unix
+    pub modified: Option<i64>,
+    /// Date du dernier accès, même unité ; ignorée par `differences` puisque la lecture la modifie
+    #[serde(default)]
+    pub accessed: Option<i64>,
+    pub is_symlink: bool,
+}
+
+impl FileIdentity {
+    /// `metadata` doit provenir de `symlink_metadata` (ou `DirEntry::metadata`) pour ne pas suivre les liens.
+    pub fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let (device, inode) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.dev(), metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let (device, inode) = (0, 0);
+
+        FileIdentity {
+            device,
+            inode,
+            size: metadata.len(),
+            modified: metadata.modified().ok()
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos() as i64),
+            accessed: metadata.accessed().ok()
+                .and_then(|accessed| accessed.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos() as i64),
+            is_symlink: metadata.file_type().is_symlink(),
+        }
+    }
+
+    pub fn of_path(path: &Path) -> std::io::Result<Self> {
+        std::fs::symlink_metadata(path).map(|metadata| Self::from_metadata(&metadata))
+    }
+
+    pub fn modified_at(&self) -> Option<DateTime<Utc>> {
+        self.modified.map(datetime_from_nanos)
+    }
+
+    /// Liste lisible des différences entre l'identité relevée et l'identité actuelle.
+    pub fn differences(&self, current: &FileIdentity) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.device != current.device || self.inode != current.inode {
+            differences.push("file was replaced (device or inode changed)".to_string());
+        }
+        if self.size != current.size {
+            differences.push(format!("size changed from {} to {} bytes", self.size, current.size));
+        }
+        if self.modified != current.modified {
+            differences.push("modification time changed".to_string());
+        }
+        if self.is_symlink != current.is_symlink {
+            differences.push("file type changed".to_string());
+        }
+        differences
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScannedFolder {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub file_count: u32,
+    /// Entrées contenues, quel que soit leur type (fichiers, dossiers, liens symboliques...) : autant d'inodes
+    #[serde(default)]
+    pub entry_count: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub exclude_patterns: Vec<String>,
+    pub min_file_size: u64,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            exclude_patterns: Vec::new(),
+            min_file_size: 100_000,
+        }
+    }
+}
+
+impl ScanOptions {
+    // Un motif sans séparateur désigne un nom d'entrée (`node_modules`, `.git`),
+    // sinon il s'agit d'un préfixe de chemin, relatif à la racine du scan s'il n'est pas absolu
+    pub fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        self.exclude_patterns.iter().any(|pattern| {
+            if pattern.contains('/') || pattern.contains('\\') {
+                path.starts_with(root.join(pattern))
+            } else {
+                path.file_name().map(|name| name == pattern.as_str()).unwrap_or(false)
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScanResults {
+    pub total_files: u32,
+    pub total_folders: u32,
+    pub total_size: u64,
+    pub scan_time: f32,
+    pub scan_path: String,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub largest_files: Vec<ScannedFile>,
+    pub folders: Vec<ScannedFolder>,
+    pub all_folders: Vec<ScannedFolder>,
+    pub file_type_distribution: HashMap<String, (u64, u32)>,
+    pub cancellation_flag: Arc<AtomicBool>,
+    pub error_logger: ErrorLogger,
+    pub has_error: bool,
+    pub error_data: Option<ErrorData>,
+    /// Octets réellement alloués sur le volume de la racine (blocs, liens physiques comptés une fois)
+    pub allocated_size: u64,
+    /// Dossiers et fichiers écartés par les motifs d'exclusion
+    pub excluded_paths: Vec<PathBuf>,
+    /// Dossiers dont le contenu n'a pas pu être lu
+    pub unreadable_paths: Vec<PathBuf>,
+    /// Histogrammes des fichiers directs de chaque dossier, par catégorie
+    pub histograms: HashMap<PathBuf, HashMap<String, FileHistograms>>,
+    /// Résumé du bilan du volume, calculé une fois par scan plutôt qu'à chaque lecture des résultats
+    pub accounting: Option<AccountingSummary>,
+}
+
+impl Default for ScanResults {
+    fn default() -> Self {
+        ScanResults {
+            total_files: 0,
+            total_folders: 0,
+            total_size: 0,
+            scan_time: 0.0,
+            scan_path: String::new(),
+            scanned_at: None,
+            largest_files: Vec::new(),
+            folders: Vec::new(),
+            all_folders: Vec::new(),
+            file_type_distribution: HashMap::new(),
+            cancellation_flag: Arc::new(AtomicBool::new(false)),
+            error_logger: ErrorLogger::new(),
+            has_error: false,
+            error_data: None,
+            allocated_size: 0,
+            excluded_paths: Vec::new(),
+            unreadable_paths: Vec::new(),
+            histograms: HashMap::new(),
+            accounting: None,
+        }
+    }
+}
+
+pub fn datetime_from_nanos(nanos: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(nanos.div_euclid(1_000_000_000), nanos.rem_euclid(1_000_000_000) as u32).unwrap_or_default()
+}
+
+// FNV-1a : stable d'une exécution et d'une version de Rust à l'autre, contrairement à DefaultHasher
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Identifiant opaque d'une entrée, dérivé du chemin et du jeton du scan qui l'a produite.
+pub fn entry_id(prefix: &str, scan_token: &str, path: &Path) -> String {
+    let hash = fnv1a(0xcbf29ce484222325, scan_token.as_bytes());
+    let hash = fnv1a(hash, path.as_os_str().as_encoded_bytes());
+    format!("{}-{:016x}", prefix, hash)
+}
+
+impl ScanResults {
+    // Les identifiants restent valables tant que le même scan (ou son snapshot) est chargé
+    fn scan_token(&self) -> String {
+        let scanned_at = self.scanned_at.map(|date| date.to_rfc3339()).unwrap_or_default();
+        format!("{}|{}", self.scan_path, scanned_at)
+    }
+
+    pub fn file_id(&self, file: &ScannedFile) -> String {
+        entry_id("f", &self.scan_token(), &file.path)
+    }
+
+    pub fn folder_id(&self, folder: &ScannedFolder) -> String {
+        entry_id("d", &self.scan_token(), &folder.path)
+    }
+
+    pub fn find_file(&self, id: &str) -> Result<&ScannedFile, CommandError> {
+        let token = self.scan_token();
+        self.largest_files.iter()
+            .find(|file| entry_id("f", &token, &file.path) == id)
+            .ok_or_else(|| CommandError::StaleId(format!("file {} is not part of the current scan", id)))
+    }
+
+    pub fn find_folder(&self, id: &str) -> Result<&ScannedFolder, CommandError> {
+        let token = self.scan_token();
+        self.all_folders.iter()
+            .find(|folder| entry_id("d", &token, &folder.path) == id)
+            .ok_or_else(|| CommandError::StaleId(format!("folder {} is not part of the current scan", id)))
+    }
+}
+
+#[derive(Clone)]
+pub struct AtomicCounters {
+    pub files_analyzed: Arc<AtomicU32>,
+    pub total_size: Arc<AtomicU64>,
+    pub folder_count: Arc<AtomicU32>,
+    pub current_path: Arc<Mutex<String>>,
+}
+
+impl AtomicCounters {
+    pub fn new() -> Self {
+        Self {
+            files_analyzed: Arc::new(AtomicU32::new(0)),
+            total_size: Arc::new(AtomicU64::new(0)),
+            folder_count: Arc::new(AtomicU32::new(0)),
+            current_path: Arc::new(Mutex::new("Starting...".to_string())),
+        }
+    }
+    
+    pub fn get_values(&self) -> (u32, u64, u32) {
+        (
+            self.files_analyzed.load(Ordering::Relaxed),
+            self.total_size.load(Ordering::Relaxed),
+            self.folder_count.load(Ordering::Relaxed),
+        )
+    }
+    
+    pub fn update_current_path(&self, path: &str) {
+        if let Ok(mut current_path) = self.current_path.lock() {
+            *current_path = path.to_string();
+        }
+    }
+}
+
+impl Default for AtomicCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ThreadScanResult {
+    pub files: Vec<ScannedFile>,
+    pub folders: Vec<ScannedFolder>,
+    pub file_type_distribution: HashMap<String, (u64, u32)>,
+    pub allocated_size: u64,
+    /// Blocs des fichiers à plusieurs liens physiques, par inode, fusionnés entre les threads
+    pub linked_blocks: HashMap<u64, u64>,
+    pub excluded_paths: Vec<PathBuf>,
+    pub unreadable_paths: Vec<PathBuf>,
+    pub histograms: HashMap<PathBuf, HashMap<String, FileHistograms>>,
+}
+
+pub type SharedScanResults = Arc<Mutex<ScanResults>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorLog {
+    pub id: u32,
+    pub timestamp: String,
+    pub level: String,
+    pub message: String,
+    pub path: Option<String>,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorData {
+    pub error_code: String,
+    pub timestamp: String,
+    pub path: String,
+    pub files_scanned: u32,
+    pub data_analyzed: u64,
+    pub scan_duration: f32,
+    pub error_logs: Vec<ErrorLog>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorLogger {
+    pub logs: Arc<Mutex<Vec<ErrorLog>>>,
+    pub current_id: Arc<AtomicU32>,
+}
+
+impl ErrorLogger {
+    pub fn new() -> Self {
+        Self {
+            logs: Arc::new(Mutex::new(Vec::new())),
+            current_id: Arc::new(AtomicU32::new(1)),
+        }
+    }
+    
+    pub fn log_info(&self, message: &str, path: Option<&str>) {
+        self.log("INFO", message, path, None);
+    }
+    
+    pub fn log_error(&self, message: &str, path: Option<&str>, error_code: Option<&str>) {
+        self.log("ERROR", message, path, error_code);
+    }
+    
+    pub fn log_warning(&self, message: &str, path: Option<&str>) {
+        self.log("WARN", message, path, None);
+    }
+    
+    fn log(&self, level: &str, message: &str, path: Option<&str>, error_code: Option<&str>) {
+        if let Ok(mut logs) = self.logs.lock() {
+            let id = self.current_id.fetch_add(1, Ordering::Relaxed);
+            let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            
+            logs.push(ErrorLog {
+                id,
+                timestamp,
+                level: level.to_string(),
+                message: message.to_string(),
+                path: path.map(|p| p.to_string()),
+                error_code: error_code.map(|c| c.to_string()),
+            });
+            
+            // Garder seulement les 1000 derniers logs
+            if logs.len() > 1000 {
+                let excess = logs.len() - 1000;
+                logs.drain(0..excess);
+            }
+        }
+    }
+    
+    pub fn get_logs(&self) -> Vec<ErrorLog> {
+        if let Ok(logs) = self.logs.lock() {
+            logs.clone()
+        } else {
+            Vec::new()
+        }
+    }
+    
+    pub fn clear_logs(&self) {
+        if let Ok(mut logs) = self.logs.lock() {
+            logs.clear();
+            self.current_id.store(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for ErrorLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
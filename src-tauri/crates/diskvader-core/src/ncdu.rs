@@ -0,0 +1,284 @@
+use chrono::DateTime;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::builder::ResultsBuilder;
+use crate::models::{CommandError, ScannedFile, ScannedFolder, ScanResults};
+
+// Nom de l'entrée qui regroupe, à l'export, les fichiers non conservés individuellement par le scan
+pub const NCDU_SMALL_FILES_ENTRY: &str = "(small files)";
+
+fn invalid(message: &str) -> CommandError {
+    CommandError::InternalError(format!("Invalid ncdu export: {}", message))
+}
+
+/// Charge un export JSON de ncdu (`ncdu -o`) dans le modèle de scan.
+pub fn import_ncdu_file(path: &Path) -> Result<ScanResults, CommandError> {
+    if !path.exists() {
+        return Err(CommandError::PathDoesNotExist(path.to_string_lossy().to_string()));
+    }
+    let content = fs::read_to_string(path)?;
+    import_ncdu_str(&content)
+}
+
+pub fn import_ncdu_str(content: &str) -> Result<ScanResults, CommandError> {
+    let document: Value = serde_json::from_str(content)
+        .map_err(|e| invalid(&e.to_string()))?;
+
+    // [majorver, minorver, metadata, root]
+    let root = match document.as_array() {
+        Some(items) if items.len() >= 4 && items[0].as_u64() == Some(1) => &items[3],
+        _ => return Err(invalid("expected [1, minor, metadata, tree]")),
+    };
+
+    let root_name = root.as_array()
+        .and_then(|items| items.first())
+        .and_then(|info| info.get("name"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("root directory has no name"))?;
+    let root_path = PathBuf::from(root_name);
+
+    let mut builder = ResultsBuilder::new();
+    import_directory(root, &root_path, &mut builder)?;
+
+    let mut results = builder.finish(&root_path);
+    results.scanned_at = document[2].get("timestamp")
+        .and_then(Value::as_i64)
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+    Ok(results)
+}
+
+// Nom d'une entrée, qui doit rester un seul composant sous son dossier parent
+fn entry_name<'a>(info: &'a Value, kind: &str) -> Result<&'a str, CommandError> {
+    let name = info.get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(&format!("{} without a name", kind)))?;
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return Err(invalid(&format!("{} name {:?} is not a single path component", kind, name)));
+    }
+    Ok(name)
+}
+
+fn is_skipped(info: &Value) -> bool {
+    info.get("excluded").is_some() || info.get("notreg").and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn import_directory(node: &Value, dir_path: &Path, builder: &mut ResultsBuilder) -> Result<(), CommandError> {
+    let items = node.as_array().ok_or_else(|| invalid("directory is not an array"))?;
+    let info = items.first().ok_or_else(|| invalid("empty directory entry"))?;
+    if is_skipped(info) {
+        return Ok(());
+    }
+
+    builder.add_directory(dir_path);
+
+    for entry in &items[1..] {
+        if entry.is_array() {
+            let info = entry.as_array()
+                .and_then(|items| items.first())
+                .ok_or_else(|| invalid("empty directory entry"))?;
+            let name = entry_name(info, "directory")?;
+            import_directory(entry, &dir_path.join(name), builder)?;
+            continue;
+        }
+
+        if is_skipped(entry) {
+            continue;
+        }
+        let name = entry_name(entry, "file")?;
+        let file_size = entry.get("asize").and_then(Value::as_u64).unwrap_or(0);
+
+        if name == NCDU_SMALL_FILES_ENTRY {
+            builder.add_aggregate(dir_path, file_size);
+        } else {
+            builder.add_file(dir_path.join(name), file_size);
+        }
+    }
+
+    Ok(())
+}
+
+/// Exporte l'arbre du scan au format JSON de ncdu.
+///
+/// Le scan ne conserve que les fichiers au-dessus du seuil de taille ; le reste du contenu
+/// direct de chaque dossier est regroupé dans une entrée `(small files)` pour que les totaux restent justes.
+pub fn export_ncdu(results: &ScanResults) -> Value {
+    let root_path = PathBuf::from(&results.scan_path);
+
+    let folders: HashMap<&Path, &ScannedFolder> = results.all_folders.iter()
+        .filter(|folder| folder.path.starts_with(&root_path))
+        .map(|folder| (folder.path.as_path(), folder))
+        .collect();
+
+    let mut children: HashMap<&Path, Vec<&Path>> = HashMap::new();
+    for path in folders.keys() {
+        if *path == root_path.as_path() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            children.entry(parent).or_default().push(path);
+        }
+    }
+
+    let mut files: HashMap<&Path, Vec<&ScannedFile>> = HashMap::new();
+    for file in &results.largest_files {
+        if let Some(parent) = file.path.parent() {
+            files.entry(parent).or_default().push(file);
+        }
+    }
+
+    let tree = export_directory(&root_path, true, &folders, &children, &files);
+
+    json!([
+        1,
+        0,
+        {
+            "progname": "diskvader",
+            "progver": env!("CARGO_PKG_VERSION"),
+            "timestamp": chrono::Utc::now().timestamp(),
+        },
+        tree
+    ])
+}
+
+fn export_directory(
+    dir_path: &Path,
+    is_root: bool,
+    folders: &HashMap<&Path, &ScannedFolder>,
+    children: &HashMap<&Path, Vec<&Path>>,
+    files: &HashMap<&Path, Vec<&ScannedFile>>,
+) -> Value {
+    let name = if is_root {
+        dir_path.to_string_lossy().to_string()
+    } else {
+        dir_path.file_name().unwrap_or_default().to_string_lossy().to_string()
+    };
+
+    let mut info = Map::new();
+    info.insert("name".to_string(), Value::from(name));
+    let mut entries = vec![Value::Object(info)];
+
+    let (recursive_size, recursive_count) = folders.get(dir_path)
+        .map(|folder| (folder.size, folder.file_count))
+        .unwrap_or((0, 0));
+    let mut direct_size = recursive_size;
+    let mut direct_count = recursive_count;
+
+    let mut subdirectories = children.get(dir_path).cloned().unwrap_or_default();
+    subdirectories.sort();
+    for child in subdirectories {
+        if let Some(folder) = folders.get(child) {
+            direct_size = direct_size.saturating_sub(folder.size);
+            direct_count = direct_count.saturating_sub(folder.file_count);
+        }
+        entries.push(export_directory(child, false, folders, children, files));
+    }
+
+    for file in files.get(dir_path).map(Vec::as_slice).unwrap_or(&[]) {
+        direct_size = direct_size.saturating_sub(file.size);
+        direct_count = direct_count.saturating_sub(1);
+        entries.push(json!({ "name": file.name, "asize": file.size }));
+    }
+
+    if direct_size > 0 || direct_count > 0 {
+        entries.push(json!({ "name": NCDU_SMALL_FILES_ENTRY, "asize": direct_size }));
+    }
+
+    Value::Array(entries)
+}
+
+pub fn export_ncdu_file(results: &ScanResults, path: &Path) -> Result<(), CommandError> {
+    let content = serde_json::to_string(&export_ncdu(results))
+        .map_err(|e| CommandError::InternalError(format!("Failed to serialize ncdu export: {}", e)))?;
+    fs::write(path, content)
+        .map_err(|e| CommandError::InternalError(format!("Failed to write ncdu export {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[1, 2, {"progname": "ncdu", "progver": "2.3", "timestamp": 1700000000},
+        [{"name": "/data"},
+            {"name": "big.iso", "asize": 300000},
+            {"name": "note.txt", "asize": 120},
+            {"name": "cache.tmp", "asize": 999, "excluded": "pattern"},
+            [{"name": "photos"},
+                {"name": "a.jpg", "asize": 150000},
+                {"name": "b.jpg", "asize": 2000}],
+            [{"name": "mnt", "excluded": "othfs"}]]]"#;
+
+    fn sizes(results: &ScanResults) -> Vec<(PathBuf, u64, u32)> {
+        let mut sizes: Vec<(PathBuf, u64, u32)> = results.all_folders.iter()
+            .map(|folder| (folder.path.clone(), folder.size, folder.file_count))
+            .collect();
+        sizes.sort();
+        sizes
+    }
+
+    #[test]
+    fn import_skips_excluded_entries() {
+        let results = import_ncdu_str(SAMPLE).unwrap();
+
+        assert_eq!(results.scan_path, "/data");
+        assert_eq!(results.scanned_at.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(results.total_files, 4);
+        assert_eq!(results.total_size, 452_120);
+        assert_eq!(sizes(&results), vec![
+            (PathBuf::from("/data"), 452_120, 4),
+            (PathBuf::from("/data/photos"), 152_000, 2),
+        ]);
+        // Seuls les fichiers au-dessus du seuil sont conservés individuellement
+        let names: Vec<&str> = results.largest_files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["big.iso", "a.jpg"]);
+    }
+
+    #[test]
+    fn export_round_trip_keeps_totals() {
+        let results = import_ncdu_str(SAMPLE).unwrap();
+        let exported = serde_json::to_string(&export_ncdu(&results)).unwrap();
+        let reimported = import_ncdu_str(&exported).unwrap();
+
+        assert_eq!(reimported.scan_path, results.scan_path);
+        assert_eq!(reimported.total_size, results.total_size);
+        assert_eq!(sizes(&reimported).iter().map(|(path, size, _)| (path, size)).collect::<Vec<_>>(),
+            sizes(&results).iter().map(|(path, size, _)| (path, size)).collect::<Vec<_>>());
+        let names: Vec<&str> = reimported.largest_files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["big.iso", "a.jpg"]);
+    }
+
+    #[test]
+    fn export_groups_small_files() {
+        let results = import_ncdu_str(SAMPLE).unwrap();
+        let exported = export_ncdu(&results);
+        let photos = exported[3].as_array().unwrap().iter()
+            .find(|entry| entry.is_array())
+            .unwrap();
+        assert_eq!(photos[0]["name"], "photos");
+        let small = photos.as_array().unwrap().iter()
+            .find(|entry| entry["name"] == NCDU_SMALL_FILES_ENTRY)
+            .unwrap();
+        assert_eq!(small["asize"], 2000);
+    }
+
+    #[test]
+    fn import_rejects_other_documents() {
+        assert!(import_ncdu_str("[2, 0, {}, [{\"name\": \"/\"}]]").is_err());
+        assert!(import_ncdu_str("{}").is_err());
+        assert!(import_ncdu_str("not json").is_err());
+    }
+
+    #[test]
+    fn import_rejects_names_leaving_their_directory() {
+        for name in ["", ".", "..", "../etc", "a/b", "/etc"] {
+            let file = format!(r#"[1, 2, {{}}, [{{"name": "/data"}}, {{"name": {:?}, "asize": 1}}]]"#, name);
+            assert!(import_ncdu_str(&file).is_err(), "file {:?}", name);
+            let directory = format!(r#"[1, 2, {{}}, [{{"name": "/data"}}, [{{"name": {:?}}}]]]"#, name);
+            assert!(import_ncdu_str(&directory).is_err(), "directory {:?}", name);
+        }
+        // Les noms qui commencent par des points restent des noms ordinaires
+        assert!(import_ncdu_str(r#"[1, 2, {}, [{"name": "/data"}, {"name": "...", "asize": 1}]]"#).is_ok());
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+
+/// Fichier supprimé mais encore ouvert par un processus : son espace n'est libéré qu'à la fermeture.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeletedOpenFile {
+    pub pid: u32,
+    pub process_name: String,
+    pub fd: u32,
+    pub path: PathBuf,
+    /// Blocs alloués, libérés à la fermeture du dernier descripteur
+    pub size: u64,
+    pub device: u64,
+    pub inode: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeletedOpenFilesReport {
+    pub files: Vec<DeletedOpenFile>,
+    /// Taille retenue, chaque fichier ne comptant qu'une fois même ouvert plusieurs fois
+    pub total_size: u64,
+    /// Processus dont les descripteurs n'ont pas pu être lus (autre utilisateur sans privilèges)
+    pub inaccessible_processes: u32,
+}
+
+/// Parcourt `/proc/*/fd` à la recherche de fichiers supprimés, limités au périphérique `device` s'il est donné.
+#[cfg(target_os = "linux")]
+pub fn deleted_open_files(device: Option<u64>) -> DeletedOpenFilesReport {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use crate::volumes::allocated_size;
+
+    let mut report = DeletedOpenFilesReport::default();
+    let Ok(processes) = fs::read_dir("/proc") else {
+        log::warn!("Cannot read /proc");
+        return report;
+    };
+    let mut counted = HashSet::new();
+
+    for process in processes.flatten() {
+        let Some(pid) = process.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let descriptors = match fs::read_dir(process.path().join("fd")) {
+            Ok(descriptors) => descriptors,
+            Err(_) => {
+                report.inaccessible_processes += 1;
+                continue;
+            }
+        };
+        let process_name = fs::read_to_string(process.path().join("comm"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default();
+
+        for descriptor in descriptors.flatten() {
+            let Ok(target) = fs::read_link(descriptor.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            // Les fichiers anonymes (`/memfd:...`) n'ont jamais eu de chemin
+            let Some(path) = target.strip_suffix(" (deleted)").filter(|path| path.starts_with('/') && !path.starts_with("/memfd:")) else {
+                continue;
+            };
+            // Le lien /proc/<pid>/fd/<n> donne encore accès au fichier supprimé
+            let Ok(metadata) = fs::metadata(descriptor.path()) else {
+                continue;
+            };
+            if !metadata.is_file() || metadata.nlink() > 0 || device.is_some_and(|device| device != metadata.dev()) {
+                continue;
+            }
+
+            // Blocs alloués : un fichier creux ne retient pas sa taille apparente
+            let size = allocated_size(&metadata);
+            if counted.insert((metadata.dev(), metadata.ino())) {
+                report.total_size += size;
+            }
+            report.files.push(DeletedOpenFile {
+                pid,
+                process_name: process_name.clone(),
+                fd: descriptor.file_name().to_str().and_then(|fd| fd.parse().ok()).unwrap_or_default(),
+                path: PathBuf::from(path),
+                size,
+                device: metadata.dev(),
+                inode: metadata.ino(),
+            });
+        }
+    }
+
+    report.files.sort_by_key(|file| std::cmp::Reverse(file.size));
+    report
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn deleted_open_files(_device: Option<u64>) -> DeletedOpenFilesReport {
+    DeletedOpenFilesReport::default()
+}
@@ -0,0 +1,45 @@
+use serde::{Serialize, Deserialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Avancement d'une opération longue sur des fichiers (compression, transfert...).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    pub operation: String,
+    pub items_done: u32,
+    pub items_total: u32,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_path: String,
+}
+
+/// Destination des événements de progression des opérations sur fichiers.
+pub trait OperationSink: Send + Sync {
+    fn emit_operation_progress(&self, progress: OperationProgress);
+}
+
+/// Sink qui ignore toute progression.
+pub struct NoopOperationSink;
+
+impl OperationSink for NoopOperationSink {
+    fn emit_operation_progress(&self, _progress: OperationProgress) {}
+}
+
+/// Indicateur d'annulation partagé par les opérations longues sur fichiers.
+#[derive(Clone, Default)]
+pub struct OperationCancellation(Arc<AtomicBool>);
+
+impl OperationCancellation {
+    pub fn flag(&self) -> &AtomicBool {
+        &self.0
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
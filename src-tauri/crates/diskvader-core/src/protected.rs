@@ -0,0 +1,314 @@
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::models::CommandError;
+
+// Répertoires système : rien à l'intérieur ne doit être supprimé, déplacé ou compressé
+const SYSTEM_DIRECTORIES: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc",
+    "/sbin", "/sys", "/usr", "/var/lib", "/var/db",
+    "/System", "/Library", "/private/etc", "/private/var/db",
+    "C:\\Windows", "C:\\Program Files", "C:\\Program Files (x86)", "C:\\ProgramData",
+];
+
+const VCS_DIRECTORIES: &[&str] = &[".git", ".hg", ".svn", ".bzr"];
+
+const BUNDLE_EXTENSIONS: &[&str] = &["app", "framework", "bundle", "kext"];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProtectedPathsInfo {
+    pub builtin: Vec<PathBuf>,
+    pub user: Vec<PathBuf>,
+}
+
+/// Chemins que les commandes destructives refusent de toucher : liste intégrée,
+/// chemins ajoutés par l'application et liste configurée par l'utilisateur.
+pub struct ProtectedPaths {
+    builtin: Vec<PathBuf>,
+    user: Vec<PathBuf>,
+    storage: Option<PathBuf>,
+}
+
+pub type SharedProtectedPaths = Arc<Mutex<ProtectedPaths>>;
+
+impl Default for ProtectedPaths {
+    fn default() -> Self {
+        let mut builtin: Vec<PathBuf> = SYSTEM_DIRECTORIES.iter().map(PathBuf::from).collect();
+        if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            let home = PathBuf::from(home);
+            builtin.push(home.join(".ssh"));
+            builtin.push(home.join(".gnupg"));
+        }
+        ProtectedPaths {
+            builtin,
+            user: Vec::new(),
+            storage: None,
+        }
+    }
+}
+
+impl ProtectedPaths {
+    /// Charge la liste utilisateur depuis `storage` ; un fichier absent ou illisible donne une liste vide.
+    pub fn load(storage: PathBuf) -> Self {
+        let user = match fs::read_to_string(&storage) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Ignoring unreadable protected path list {}: {}", storage.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        ProtectedPaths {
+            user,
+            storage: Some(storage),
+            ..ProtectedPaths::default()
+        }
+    }
+
+    /// Ajoute un chemin à la liste intégrée (par exemple le dossier de données de l'application).
+    pub fn protect(&mut self, path: PathBuf) {
+        if !self.builtin.contains(&path) {
+            self.builtin.push(path);
+        }
+    }
+
+    pub fn info(&self) -> ProtectedPathsInfo {
+        ProtectedPathsInfo {
+            builtin: self.builtin.clone(),
+            user: self.user.clone(),
+        }
+    }
+
+    pub fn set_user_paths(&mut self, paths: Vec<PathBuf>) -> Result<(), CommandError> {
+        self.user = paths;
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        if let Some(parent) = storage.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.user)
+            .map_err(|e| CommandError::InternalError(format!("Failed to serialize protected paths: {}", e)))?;
+        fs::write(storage, content)?;
+        Ok(())
+    }
+
+    /// Raison pour laquelle `path` est protégé, ou `None` s'il peut être modifié.
+    /// Les liens symboliques des dossiers parents sont résolus avant la comparaison, et un dossier
+    /// est refusé s'il contient un chemin protégé, un dépôt de gestion de versions ou un autre volume.
+    pub fn violation(&self, path: &Path) -> Option<String> {
+        if !path.is_absolute() {
+            return Some("relative path".to_string());
+        }
+        if path.components().any(|component| component == Component::ParentDir) {
+            return Some("path with a parent directory component".to_string());
+        }
+        let path = resolve(path);
+        if path.parent().is_none() {
+            return Some("filesystem root".to_string());
+        }
+
+        let entries = self.builtin.iter().map(|entry| (entry, "protected location"))
+            .chain(self.user.iter().map(|entry| (entry, "user protected location")));
+        for (entry, kind) in entries {
+            let canonical = fs::canonicalize(entry).unwrap_or_else(|_| entry.clone());
+            if path.starts_with(entry) || path.starts_with(&canonical) {
+                return Some(format!("inside {} {}", kind, entry.display()));
+            }
+            // Supprimer un dossier parent (le dossier personnel par exemple) emporterait l'entrée protégée
+            if entry.starts_with(&path) || canonical.starts_with(&path) {
+                return Some(format!("contains {} {}", kind, entry.display()));
+            }
+        }
+
+        for component in path.components() {
+            let name = component.as_os_str().to_string_lossy();
+            if VCS_DIRECTORIES.contains(&name.as_ref()) {
+                return Some(format!("inside version control directory {}", name));
+            }
+            let is_bundle = Path::new(name.as_ref()).extension()
+                .map(|extension| BUNDLE_EXTENSIONS.iter().any(|bundle| extension.eq_ignore_ascii_case(bundle)))
+                .unwrap_or(false);
+            if is_bundle {
+                return Some(format!("inside application bundle {}", name));
+            }
+        }
+
+        if is_mount_point(&path) {
+            return Some("mount point".to_string());
+        }
+
+        nested_violation(&path)
+    }
+}
+
+// Chemin absolu dont les dossiers parents sont résolus ; le dernier composant n'est pas suivi,
+// supprimer un lien symbolique ne touchant que le lien. La partie qui n'existe pas encore est gardée telle quelle.
+fn resolve(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    while let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) {
+        missing.push(name);
+        existing = parent;
+        if let Ok(canonical) = fs::canonicalize(existing) {
+            return missing.iter().rev().fold(canonical, |resolved, name| resolved.join(name));
+        }
+    }
+    path.to_path_buf()
+}
+
+// Dépôt ou point de montage à l'intérieur du dossier `root`, sans suivre les liens symboliques
+fn nested_violation(root: &Path) -> Option<String> {
+    let metadata = fs::symlink_metadata(root).ok().filter(|metadata| metadata.is_dir())?;
+    let mut pending = vec![(root.to_path_buf(), device(&metadata))];
+    while let Some((directory, parent_device)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Un `.git` peut aussi être un fichier (sous-module, worktree)
+            if VCS_DIRECTORIES.iter().any(|vcs| entry.file_name() == *vcs) {
+                return Some(format!("contains version control directory {}", path.display()));
+            }
+            let Ok(metadata) = path.symlink_metadata() else {
+                continue;
+            };
+            if !metadata.is_dir() {
+                continue;
+            }
+            if device(&metadata) != parent_device {
+                return Some(format!("contains mount point {}", path.display()));
+            }
+            pending.push((path, parent_device));
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn device(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn is_mount_point(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    if !metadata.is_dir() {
+        return false;
+    }
+    match path.parent().map(fs::metadata) {
+        Some(Ok(parent)) => parent.dev() != metadata.dev() || parent.ino() == metadata.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_mount_point(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("diskvader-protected-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::canonicalize(root).unwrap()
+    }
+
+    #[test]
+    fn system_and_relative_paths_are_refused() {
+        let protected = ProtectedPaths::default();
+        assert!(protected.violation(Path::new("/etc/passwd")).is_some());
+        assert!(protected.violation(Path::new("/usr")).is_some());
+        assert!(protected.violation(Path::new("/")).is_some());
+        assert!(protected.violation(Path::new("relative/file.txt")).is_some());
+        assert!(protected.violation(Path::new("/tmp/../etc/passwd")).is_some());
+    }
+
+    #[test]
+    fn ancestors_of_protected_entries_are_refused() {
+        let root = temp_root("ancestor");
+        fs::create_dir_all(root.join("data/app")).unwrap();
+        let mut protected = ProtectedPaths::default();
+        protected.protect(root.join("data/app"));
+
+        assert!(protected.violation(&root.join("data/app/settings.json")).unwrap().starts_with("inside"));
+        assert!(protected.violation(&root.join("data")).unwrap().starts_with("contains"));
+        assert!(protected.violation(&root).unwrap().starts_with("contains"));
+        assert!(protected.violation(&root.join("data/other")).is_none());
+
+        if let Some(home) = std::env::var_os("HOME") {
+            assert!(protected.violation(Path::new(&home)).is_some());
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symbolic_links_are_resolved_before_comparing() {
+        let root = temp_root("symlink");
+        fs::create_dir_all(root.join("real/secrets")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("alias")).unwrap();
+
+        // Entrée protégée par son vrai chemin, cible passant par le lien
+        let mut protected = ProtectedPaths::default();
+        protected.protect(root.join("real/secrets"));
+        assert!(protected.violation(&root.join("alias/secrets/key")).is_some());
+        assert!(protected.violation(&root.join("alias/secrets")).is_some());
+
+        // Entrée protégée à travers le lien, cible donnée par son vrai chemin
+        let mut protected = ProtectedPaths::default();
+        protected.set_user_paths(vec![root.join("alias/secrets")]).unwrap();
+        assert!(protected.violation(&root.join("real/secrets/key")).is_some());
+
+        // Le lien lui-même peut être supprimé sans toucher à sa cible
+        let mut protected = ProtectedPaths::default();
+        protected.protect(root.join("real"));
+        assert!(protected.violation(&root.join("alias")).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn directories_containing_repositories_are_refused() {
+        let root = temp_root("vcs");
+        fs::create_dir_all(root.join("projects/app/.git/objects")).unwrap();
+        fs::create_dir_all(root.join("projects/app/src")).unwrap();
+        fs::create_dir_all(root.join("projects/sub")).unwrap();
+        fs::write(root.join("projects/sub/.git"), "gitdir: ../app/.git/modules/sub").unwrap();
+        fs::create_dir_all(root.join("downloads/old")).unwrap();
+        let protected = ProtectedPaths::default();
+
+        assert!(protected.violation(&root.join("projects/app/.git/objects")).unwrap().starts_with("inside version control"));
+        assert!(protected.violation(&root.join("projects")).unwrap().starts_with("contains version control"));
+        assert!(protected.violation(&root.join("projects/sub")).is_some());
+        assert!(protected.violation(&root.join("projects/app/src")).is_none());
+        assert!(protected.violation(&root.join("downloads")).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn missing_targets_are_resolved_through_their_existing_parent() {
+        let root = temp_root("missing");
+        let mut protected = ProtectedPaths::default();
+        protected.protect(root.join("vault"));
+
+        assert!(protected.violation(&root.join("vault/not/yet/created.txt")).is_some());
+        assert!(protected.violation(&root.join("elsewhere/created.txt")).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,354 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::analyzers;
+use crate::models::{CleanupSuggestionItem, FileTypeDistributionItem, ScanResults};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportOptions {
+    pub format: ReportFormat,
+    pub top_files: usize,
+    pub top_folders: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            format: ReportFormat::Text,
+            top_files: 10,
+            top_folders: 10,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub generated_at: DateTime<Utc>,
+    pub scan_path: String,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub total_files: u32,
+    pub total_folders: u32,
+    pub total_size: u64,
+    pub scan_time: f32,
+    pub largest_files: Vec<ReportFile>,
+    pub largest_folders: Vec<ReportFolder>,
+    pub file_types: Vec<FileTypeDistributionItem>,
+    pub cleanup_suggestions: Vec<CleanupSuggestionItem>,
+}
+
+#[derive(Serialize)]
+pub struct ReportFile {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    #[serde(rename = "type")]
+    pub file_type: String,
+}
+
+#[derive(Serialize)]
+pub struct ReportFolder {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub file_count: u32,
+    pub percentage: f32,
+}
+
+impl Report {
+    pub fn from_results(results: &ScanResults, top_files: usize, top_folders: usize) -> Self {
+        let scan_root = Path::new(&results.scan_path);
+
+        let largest_files = results.largest_files.iter()
+            .take(top_files)
+            .map(|file| ReportFile {
+                name: file.name.clone(),
+                path: file.path.to_string_lossy().to_string(),
+                size: file.size,
+                file_type: file.file_type.clone(),
+            })
+            .collect();
+
+        // La racine elle-même porte la totalité du scan, seuls ses sous-dossiers sont classés
+        let mut folders: Vec<_> = results.all_folders.iter()
+            .filter(|folder| folder.path.starts_with(scan_root) && folder.path != scan_root)
+            .collect();
+        folders.sort_by_key(|folder| Reverse(folder.size));
+
+        let largest_folders = folders.into_iter()
+            .take(top_folders)
+            .map(|folder| ReportFolder {
+                name: folder.name.clone(),
+                path: folder.path.to_string_lossy().to_string(),
+                size: folder.size,
+                file_count: folder.file_count,
+                percentage: if results.total_size > 0 {
+                    (folder.size as f32 / results.total_size as f32) * 100.0
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+
+        Report {
+            generated_at: Utc::now(),
+            scan_path: results.scan_path.clone(),
+            scanned_at: results.scanned_at,
+            total_files: results.total_files,
+            total_folders: results.total_folders,
+            total_size: results.total_size,
+            scan_time: results.scan_time,
+            largest_files,
+            largest_folders,
+            file_types: analyzers::file_type_distribution(results),
+            cleanup_suggestions: analyzers::cleanup_suggestions(results),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => render_text(self),
+            ReportFormat::Json => render_json(self),
+            ReportFormat::Csv => render_csv(self),
+            ReportFormat::Html => render_html(self),
+        }
+    }
+
+    fn scan_date(&self) -> String {
+        self.scanned_at
+            .unwrap_or(self.generated_at)
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string()
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
+pub fn render_json(report: &Report) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+}
+
+pub fn render_text(report: &Report) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "=== DISK ANALYSIS REPORT ===\n");
+
+    let _ = writeln!(out, "Scan Path: {}", report.scan_path);
+    let _ = writeln!(out, "Scan Date: {}", report.scan_date());
+    let _ = writeln!(out, "Scan Duration: {:.2} seconds", report.scan_time);
+    let _ = writeln!(out, "Total Files: {}", report.total_files);
+    let _ = writeln!(out, "Total Folders: {}", report.total_folders);
+    let _ = writeln!(out, "Total Size: {:.2} GB\n", report.total_size as f64 / 1_000_000_000.0);
+
+    let _ = writeln!(out, "=== TOP {} LARGEST FILES ===", report.largest_files.len());
+    for (index, file) in report.largest_files.iter().enumerate() {
+        let _ = writeln!(out, "{}. {} - {:.2} MB ({})", index + 1, file.name, file.size as f64 / 1_000_000.0, file.path);
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "=== TOP {} LARGEST FOLDERS ===", report.largest_folders.len());
+    for (index, folder) in report.largest_folders.iter().enumerate() {
+        let _ = writeln!(out, "{}. {} - {:.2} GB ({} files)", index + 1, folder.path, folder.size as f64 / 1_000_000_000.0, folder.file_count);
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "=== FILE TYPE DISTRIBUTION ===");
+    for item in &report.file_types {
+        let _ = writeln!(out, "{}: {:.2} GB ({} files)", item.file_type, item.size as f64 / 1_000_000_000.0, item.count);
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "=== CLEANUP SUGGESTIONS ===");
+    for item in &report.cleanup_suggestions {
+        let _ = writeln!(out, "{}: {:.2} GB ({} items)", item.cleanup_type, item.size as f64 / 1_000_000_000.0, item.count);
+    }
+
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Un seul tableau pour les fichiers et les dossiers, distingués par la colonne `kind`
+pub fn render_csv(report: &Report) -> String {
+    let mut out = String::from("kind,rank,name,path,size,file_count,type,percentage\n");
+    for (index, file) in report.largest_files.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "file,{},{},{},{},,{},",
+            index + 1,
+            csv_field(&file.name),
+            csv_field(&file.path),
+            file.size,
+            csv_field(&file.file_type),
+        );
+    }
+    for (index, folder) in report.largest_folders.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "folder,{},{},{},{},{},,{:.2}",
+            index + 1,
+            csv_field(&folder.name),
+            csv_field(&folder.path),
+            folder.size,
+            folder.file_count,
+            folder.percentage,
+        );
+    }
+    out
+}
+
+fn html_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Graphique en barres horizontales SVG, sans dépendance externe pour que le fichier reste autonome
+fn svg_bar_chart(bars: &[(String, u64, &str)]) -> String {
+    const ROW_HEIGHT: usize = 28;
+    const LABEL_WIDTH: usize = 220;
+    const BAR_WIDTH: f64 = 420.0;
+
+    let max = bars.iter().map(|(_, value, _)| *value).max().unwrap_or(0).max(1);
+    let height = bars.len() * ROW_HEIGHT + 8;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" role=\"img\">",
+        LABEL_WIDTH + BAR_WIDTH as usize + 110,
+        height,
+    );
+    for (index, (label, value, color)) in bars.iter().enumerate() {
+        let y = index * ROW_HEIGHT + 4;
+        let width = (*value as f64 / max as f64 * BAR_WIDTH).max(1.0);
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" class=\"label\">{}</text>\
+             <rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"20\" rx=\"3\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{}\" class=\"value\">{}</text>",
+            LABEL_WIDTH - 8, y + 15, html_escape(label),
+            LABEL_WIDTH, y, width, color,
+            LABEL_WIDTH as f64 + width + 6.0, y + 15, format_size(*value),
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+pub fn render_html(report: &Report) -> String {
+    let mut body = String::new();
+
+    let _ = write!(
+        body,
+        "<h1>Disk analysis report</h1>\
+         <p class=\"meta\">{} &middot; scanned {} &middot; {} files, {} folders, {} in {:.2} s</p>",
+        html_escape(&report.scan_path),
+        report.scan_date(),
+        report.total_files,
+        report.total_folders,
+        format_size(report.total_size),
+        report.scan_time,
+    );
+
+    let type_bars: Vec<(String, u64, &str)> = report.file_types.iter()
+        .map(|item| (item.file_type.clone(), item.size, item.color.as_str()))
+        .collect();
+    let _ = write!(body, "<h2>File type distribution</h2>{}", svg_bar_chart(&type_bars));
+
+    let folder_bars: Vec<(String, u64, &str)> = report.largest_folders.iter()
+        .map(|folder| (folder.name.clone(), folder.size, "#3b82f6"))
+        .collect();
+    let _ = write!(body, "<h2>Largest folders</h2>{}", svg_bar_chart(&folder_bars));
+
+    body.push_str("<table><thead><tr><th>#</th><th>Folder</th><th>Size</th><th>Share</th><th>Files</th></tr></thead><tbody>");
+    for (index, folder) in report.largest_folders.iter().enumerate() {
+        let _ = write!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td></tr>",
+            index + 1, html_escape(&folder.path), format_size(folder.size), folder.percentage, folder.file_count,
+        );
+    }
+    body.push_str("</tbody></table>");
+
+    body.push_str("<h2>Largest files</h2><table><thead><tr><th>#</th><th>File</th><th>Size</th><th>Type</th></tr></thead><tbody>");
+    for (index, file) in report.largest_files.iter().enumerate() {
+        let _ = write!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            index + 1, html_escape(&file.path), format_size(file.size), html_escape(&file.file_type),
+        );
+    }
+    body.push_str("</tbody></table>");
+
+    body.push_str("<h2>Cleanup suggestions</h2><table><thead><tr><th>Category</th><th>Size</th><th>Items</th></tr></thead><tbody>");
+    for item in &report.cleanup_suggestions {
+        let _ = write!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            html_escape(&item.cleanup_type), format_size(item.size), item.count,
+        );
+    }
+    body.push_str("</tbody></table>");
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\">\
+         <title>DiskVader report - {}</title><style>\
+         body{{font-family:system-ui,sans-serif;margin:2rem;color:#111827}}\
+         .meta{{color:#6b7280}}\
+         table{{border-collapse:collapse;margin:1rem 0;width:100%}}\
+         th,td{{border-bottom:1px solid #e5e7eb;padding:.35rem .6rem;text-align:left}}\
+         td:nth-child(n+3){{white-space:nowrap}}\
+         svg .label{{font-size:12px;fill:#374151}} svg .value{{font-size:12px;fill:#6b7280}}\
+         </style></head><body>{}</body></html>\n",
+        html_escape(&report.scan_path),
+        body,
+    )
+}
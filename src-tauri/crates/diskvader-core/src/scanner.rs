@@ -0,0 +1,831 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+use std::sync::Arc;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::histograms::FileHistograms;
+use crate::volumes::{allocated_size, device_of};
+use crate::models::{CommandError, ScanProgress, ScanOptions, FileIdentity, ScannedFile, ScannedFolder, AtomicCounters, ThreadScanResult, SharedScanResults, ScanResults, ErrorData};
+
+/// Destination des événements de progression émis pendant un scan.
+///
+/// L'application Tauri les relaie vers la fenêtre, la CLI les affiche sur le terminal.
+pub trait ProgressSink: Send + Sync {
+    fn emit_progress(&self, progress: ScanProgress);
+}
+
+/// Sink qui ignore toute progression (tests, exécutions silencieuses).
+pub struct NoopProgressSink;
+
+impl ProgressSink for NoopProgressSink {
+    fn emit_progress(&self, _progress: ScanProgress) {}
+}
+
+// Contexte partagé par tous les threads d'un même scan
+struct ScanContext {
+    root_path: PathBuf,
+    options: ScanOptions,
+    counters: AtomicCounters,
+    sink: Arc<dyn ProgressSink>,
+    cancellation_flag: Arc<AtomicBool>,
+    estimated_total_size: u64,
+    // Seuls les blocs de ce volume comptent dans l'espace alloué
+    root_device: Option<u64>,
+    // Référence des âges : le même instant pour tous les fichiers
+    started_at: SystemTime,
+}
+
+// Résultats accumulés localement par un thread de travail
+#[derive(Default)]
+struct WorkerState {
+    files: Vec<ScannedFile>,
+    folders: Vec<ScannedFolder>,
+    file_type_distribution: HashMap<String, (u64, u32)>,
+    files_processed_since_emit: u32,
+    last_emit_percentage: f64,
+    allocated_size: u64,
+    linked_blocks: HashMap<u64, u64>,
+    excluded_paths: Vec<PathBuf>,
+    unreadable_paths: Vec<PathBuf>,
+    histograms: HashMap<PathBuf, HashMap<String, FileHistograms>>,
+}
+
+/// Lance un scan complet de `path` et stocke le résultat dans `scan_results`.
+///
+/// C'est le point d'entrée commun à la commande `start_scan` et à la CLI.
+pub fn run_scan(path: &str, options: &ScanOptions, scan_results: &SharedScanResults, sink: Arc<dyn ProgressSink>) -> Result<(), CommandError> {
+    log::info!("Starting multithreaded scan on: {}", path);
+
+    let scan_path = Path::new(path);
+    if !scan_path.exists() {
+        return Err(CommandError::PathDoesNotExist(path.to_string()));
+    }
+
+    if !scan_path.is_dir() {
+        return Err(CommandError::PathIsNotDirectory(path.to_string()));
+    }
+
+    let cancellation_flag = {
+        let mut results = scan_results.lock().unwrap();
+        *results = ScanResults::default();
+        results.scan_path = path.to_string();
+        results.cancellation_flag.store(false, Ordering::Relaxed);
+
+        // Logger le début du scan
+        results.error_logger.log_info(&format!("Starting disk scan for path: {}", path), Some(path));
+
+        results.cancellation_flag.clone()
+    };
+
+    let start_time = Instant::now();
+
+    // Estimation rapide sans double traversée
+    let estimated_total_size = estimate_total_size_fast(scan_path);
+    log::info!("Estimated total size: {:.2} GB", estimated_total_size as f64 / 1_000_000_000.0);
+
+    let counters = AtomicCounters::new();
+
+    sink.emit_progress(ScanProgress {
+        files_analyzed: 0,
+        total_size: 0,
+        folders_analyzed: 0,
+        current_path: "Collecting directories...".to_string(),
+        progress_percentage: 0.0,
+        estimated_total_size,
+    });
+
+    match scan_directory_optimized(
+        &sink,
+        scan_path,
+        options,
+        counters.clone(),
+        cancellation_flag,
+        estimated_total_size,
+    ) {
+        Ok(thread_results) => {
+            let elapsed = start_time.elapsed().as_secs_f32();
+            let (total_files, total_size, total_folders) = counters.get_values();
+
+            log::info!("Multithreaded scan completed in {:.2} seconds", elapsed);
+            log::info!("Total files analyzed: {}", total_files);
+            log::info!("Total size: {} bytes", total_size);
+            log::info!("Total folders: {}", total_folders);
+
+            let mut all_files = Vec::new();
+            let mut all_folders = Vec::new();
+            let mut combined_file_type_distribution: HashMap<String, (u64, u32)> = HashMap::new();
+            let mut allocated_size = 0u64;
+            let mut linked_blocks: HashMap<u64, u64> = HashMap::new();
+            let mut excluded_paths = Vec::new();
+            let mut unreadable_paths = Vec::new();
+            let mut histograms = HashMap::new();
+
+            for result in thread_results {
+                all_files.extend(result.files);
+                all_folders.extend(result.folders);
+                allocated_size += result.allocated_size;
+                linked_blocks.extend(result.linked_blocks);
+                excluded_paths.extend(result.excluded_paths);
+                unreadable_paths.extend(result.unreadable_paths);
+                histograms.extend(result.histograms);
+
+                for (file_type, (size, count)) in result.file_type_distribution {
+                    let entry = combined_file_type_distribution.entry(file_type).or_insert((0, 0));
+                    entry.0 += size;
+                    entry.1 += count;
+                }
+            }
+
+            all_files.sort_by_key(|file| Reverse(file.size));
+            allocated_size += linked_blocks.values().sum::<u64>();
+            excluded_paths.sort();
+            unreadable_paths.sort();
+
+            let all_folders_recursive = calculate_recursive_folder_data(all_folders);
+
+            {
+                let mut results = scan_results.lock().unwrap();
+                results.error_logger.log_info(&format!("Scan completed successfully: {} files, {} folders", total_files, total_folders), Some(path));
+
+                *results = ScanResults {
+                    total_files,
+                    total_folders,
+                    total_size,
+                    scan_time: elapsed,
+                    scan_path: path.to_string(),
+                    scanned_at: Some(chrono::Utc::now()),
+                    largest_files: all_files,
+                    folders: all_folders_recursive.clone(),
+                    all_folders: all_folders_recursive,
+                    file_type_distribution: combined_file_type_distribution,
+                    cancellation_flag: Arc::new(AtomicBool::new(false)),
+                    error_logger: results.error_logger.clone(),
+                    has_error: false,
+                    error_data: None,
+                    allocated_size,
+                    excluded_paths,
+                    unreadable_paths,
+                    histograms,
+                    accounting: None,
+                };
+            }
+
+            sink.emit_progress(ScanProgress {
+                files_analyzed: total_files,
+                total_size,
+                folders_analyzed: total_folders,
+                current_path: "Scan completed!".to_string(),
+                progress_percentage: 100.0,
+                estimated_total_size,
+            });
+
+            Ok(())
+        }
+        Err(e) => {
+            let elapsed = start_time.elapsed().as_secs_f32();
+            let (files_scanned, data_analyzed, _) = counters.get_values();
+
+            // Logger l'erreur et créer les données d'erreur
+            {
+                let mut results = scan_results.lock().unwrap();
+                results.error_logger.log_error(&format!("Scan terminated due to error: {}", e), Some(path), Some("ERR_SCAN_FAILED"));
+                results.error_logger.log_info(&format!("Partial scan completed: {} files analyzed", files_scanned), None);
+
+                results.has_error = true;
+                results.error_data = Some(ErrorData {
+                    error_code: "ERR_SCAN_FAILED".to_string(),
+                    timestamp: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    path: path.to_string(),
+                    files_scanned,
+                    data_analyzed,
+                    scan_duration: elapsed,
+                    error_logs: results.error_logger.get_logs(),
+                });
+            }
+
+            sink.emit_progress(ScanProgress {
+                files_analyzed: files_scanned,
+                total_size: data_analyzed,
+                folders_analyzed: 0,
+                current_path: "Scan failed!".to_string(),
+                progress_percentage: 0.0,
+                estimated_total_size,
+            });
+
+            Err(CommandError::ScanFailed(e.to_string()))
+        }
+    }
+}
+
+pub fn calculate_recursive_folder_data(
+    folders: Vec<ScannedFolder>,
+) -> Vec<ScannedFolder> {
+    // Racine commune : les tailles remontent jusqu'à elle, y compris à travers les dossiers sans fichier direct
+    let mut root = match folders.first() {
+        Some(folder) => folder.path.clone(),
+        None => return Vec::new(),
+    };
+    for folder in &folders {
+        while !folder.path.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+
+    let mut path_data: HashMap<PathBuf, (u64, u32, u32)> = HashMap::new();
+    for folder in &folders {
+        for ancestor in folder.path.ancestors() {
+            if !ancestor.starts_with(&root) {
+                break;
+            }
+            let entry = path_data.entry(ancestor.to_path_buf()).or_insert((0, 0, 0));
+            entry.0 += folder.size;
+            entry.1 += folder.file_count;
+            entry.2 += folder.entry_count;
+        }
+    }
+
+    let mut updated_folders: Vec<ScannedFolder> = path_data.into_iter()
+        .map(|(folder_path, (recursive_size, recursive_count, recursive_entries))| {
+            let name = folder_path.file_name()
+                .unwrap_or_else(|| folder_path.as_os_str())
+                .to_string_lossy()
+                .to_string();
+            ScannedFolder {
+                name,
+                path: folder_path,
+                size: recursive_size,
+                file_count: recursive_count,
+                entry_count: recursive_entries,
+            }
+        })
+        .collect();
+    updated_folders.sort_by_key(|f| f.path.clone());
+    updated_folders
+}
+
+pub fn scan_directory_optimized(
+    sink: &Arc<dyn ProgressSink>,
+    root_path: &Path,
+    options: &ScanOptions,
+    counters: AtomicCounters,
+    cancellation_flag: Arc<AtomicBool>,
+    estimated_total_size: u64,
+) -> Result<Vec<ThreadScanResult>, CommandError> {
+    let num_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .clamp(2, 8); // Limiter à 8 threads max
+
+    log::info!("Using {} threads for optimized scanning", num_threads);
+
+    let (tx, rx) = std::sync::mpsc::channel::<PathBuf>();
+    let rx = Arc::new(std::sync::Mutex::new(rx));
+
+    // Thread producteur pour découvrir les répertoires
+    let producer_cancellation = Arc::clone(&cancellation_flag);
+    let root_path_clone = root_path.to_path_buf();
+    let producer_options = options.clone();
+    let producer_handle = std::thread::spawn(move || {
+        discover_directories_efficiently(&root_path_clone, &producer_options, &tx, &producer_cancellation)
+    });
+
+    let context = Arc::new(ScanContext {
+        root_path: root_path.to_path_buf(),
+        options: options.clone(),
+        counters,
+        sink: Arc::clone(sink),
+        cancellation_flag,
+        estimated_total_size,
+        root_device: device_of(root_path),
+        started_at: SystemTime::now(),
+    });
+
+    // Threads consommateurs
+    let mut handles = Vec::new();
+    for thread_id in 0..num_threads {
+        let rx_clone = Arc::clone(&rx);
+        let context = Arc::clone(&context);
+
+        let handle = std::thread::spawn(move || {
+            worker_thread_optimized(thread_id, rx_clone, &context)
+        });
+        handles.push(handle);
+    }
+
+    // Attendre le producteur, qui rapporte les dossiers exclus
+    let excluded_directories = producer_handle.join().unwrap_or_default();
+
+    // Attendre tous les consommateurs
+    let mut all_results = vec![ThreadScanResult {
+        excluded_paths: excluded_directories,
+        ..ThreadScanResult::default()
+    }];
+    for handle in handles {
+        match handle.join() {
+            Ok(result) => all_results.push(result),
+            Err(e) => log::error!("Thread panicked: {:?}", e),
+        }
+    }
+
+    log::info!("Collected results from {} optimized thread(s)", all_results.len() - 1);
+    Ok(all_results)
+}
+
+pub fn get_file_type(extension: &str) -> String {
+    match extension {
+        "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" | "m4v" => "Video Files".to_string(),
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tiff" | "svg" | "webp" | "raw" | "psd" => "Images".to_string(),
+        "pdf" | "doc" | "docx" | "txt" | "rtf" | "odt" | "pages" => "Documents".to_string(),
+        "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "xz" => "Archives".to_string(),
+        "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "wma" => "Audio Files".to_string(),
+        "exe" | "app" | "deb" | "rpm" | "dmg" | "msi" => "Applications".to_string(),
+        _ => "Other".to_string(),
+    }
+}
+
+pub fn discover_directories_efficiently(
+    root_path: &Path,
+    options: &ScanOptions,
+    tx: &std::sync::mpsc::Sender<PathBuf>,
+    cancellation_flag: &Arc<AtomicBool>,
+) -> Vec<PathBuf> {
+    let mut stack = vec![root_path.to_path_buf()];
+    let mut excluded = Vec::new();
+
+    while let Some(current_path) = stack.pop() {
+        if cancellation_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if tx.send(current_path.clone()).is_err() {
+            break; // Canal fermé
+        }
+
+        if let Ok(entries) = fs::read_dir(&current_path) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                if options.is_excluded(root_path, &path) {
+                    excluded.push(path);
+                } else {
+                    stack.push(path);
+                }
+            }
+        }
+    }
+
+    excluded
+}
+
+fn worker_thread_optimized(
+    thread_id: usize,
+    rx: Arc<std::sync::Mutex<std::sync::mpsc::Receiver<PathBuf>>>,
+    context: &ScanContext,
+) -> ThreadScanResult {
+    log::debug!("Optimized worker thread {} started", thread_id);
+
+    let mut state = WorkerState::default();
+
+    // Réserver de l'espace pour éviter les réallocations
+    state.files.reserve(1000);
+    state.folders.reserve(100);
+
+    loop {
+        if context.cancellation_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let directory = {
+            let receiver = rx.lock().unwrap();
+            receiver.recv()
+        };
+
+        match directory {
+            Ok(dir_path) => {
+                if let Err(e) = scan_single_directory_optimized(&dir_path, &mut state, context) {
+                    log::warn!("Thread {} error scanning {:?}: {}", thread_id, dir_path, e);
+                }
+            },
+            Err(_) => break, // Canal fermé, plus de répertoires
+        }
+    }
+
+    log::debug!("Optimized worker thread {} finished", thread_id);
+
+    ThreadScanResult {
+        files: state.files,
+        folders: state.folders,
+        file_type_distribution: state.file_type_distribution,
+        allocated_size: state.allocated_size,
+        linked_blocks: state.linked_blocks,
+        excluded_paths: state.excluded_paths,
+        unreadable_paths: state.unreadable_paths,
+        histograms: state.histograms,
+    }
+}
+
+// Ajoute les blocs d'une entrée du volume racine ; un fichier à plusieurs liens n'est compté qu'une fois
+fn record_allocation(metadata: &fs::Metadata, state: &mut WorkerState, context: &ScanContext) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if context.root_device != Some(metadata.dev()) {
+            return;
+        }
+        if metadata.nlink() > 1 && !metadata.is_dir() {
+            state.linked_blocks.insert(metadata.ino(), allocated_size(metadata));
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = context;
+
+    state.allocated_size += allocated_size(metadata);
+}
+
+fn scan_single_directory_optimized(
+    dir_path: &Path,
+    state: &mut WorkerState,
+    context: &ScanContext,
+) -> Result<(), CommandError> {
+    let counters = &context.counters;
+    let cancellation_flag = &context.cancellation_flag;
+    let estimated_total_size = context.estimated_total_size;
+
+    if cancellation_flag.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let entries = match fs::read_dir(dir_path) {
+        Ok(entries) => entries,
+        Err(_) => {
+            // Contenu inconnu : rapporté par le bilan d'occupation du disque
+            state.unreadable_paths.push(dir_path.to_path_buf());
+            return Ok(());
+        }
+    };
+
+    if let Ok(metadata) = fs::symlink_metadata(dir_path) {
+        record_allocation(&metadata, state, context);
+    }
+
+    counters.folder_count.fetch_add(1, Ordering::Relaxed);
+    counters.update_current_path(&dir_path.to_string_lossy());
+
+    let mut current_folder_size = 0u64;
+    let mut current_folder_file_count = 0u32;
+    let mut current_folder_entry_count = 0u32;
+    let mut current_folder_histograms: HashMap<String, FileHistograms> = HashMap::new();
+
+    for entry in entries {
+        if cancellation_flag.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue, // Ignorer les erreurs d'entrée
+        };
+        let entry_path = entry.path();
+
+        let is_file = entry_path.is_file();
+        if context.options.is_excluded(&context.root_path, &entry_path) {
+            // Les dossiers exclus sont déjà relevés par le parcours des dossiers
+            if is_file {
+                state.excluded_paths.push(entry_path);
+            }
+            continue;
+        }
+        current_folder_entry_count += 1;
+
+        if is_file {
+            if let Ok(metadata) = entry.metadata() {
+                record_allocation(&metadata, state, context);
+                let file_size = metadata.len();
+                counters.files_analyzed.fetch_add(1, Ordering::Relaxed);
+                counters.total_size.fetch_add(file_size, Ordering::Relaxed);
+                current_folder_size += file_size;
+                current_folder_file_count += 1;
+
+                let extension = entry_path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+
+                let file_type = get_file_type_cached(&extension);
+
+                let counter = state.file_type_distribution.entry(file_type.clone()).or_insert((0, 0));
+                counter.0 += file_size;
+                counter.1 += 1;
+                current_folder_histograms.entry(file_type.clone()).or_default().record(&metadata, context.started_at);
+
+                // Stocker seulement les fichiers significatifs
+                if file_size > context.options.min_file_size {
+                    state.files.push(ScannedFile {
+                        name: entry_path.file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .to_string(),
+                        path: entry_path,
+                        size: file_size,
+                        file_type,
+                        extension,
+                        identity: Some(FileIdentity::from_metadata(&metadata)),
+                    });
+                }
+
+                // Émission de progrès optimisée
+                state.files_processed_since_emit += 1;
+                if state.files_processed_since_emit >= 500 { // Réduire la fréquence d'émission
+                    let (total_files, total_size, total_folders) = counters.get_values();
+                    let progress_percentage = if estimated_total_size > 0 {
+                        (total_size as f64 / estimated_total_size as f64 * 100.0).min(99.9)
+                    } else {
+                        0.0
+                    };
+
+                    if (progress_percentage - state.last_emit_percentage).abs() >= 1.0 {
+                        let progress = ScanProgress {
+                            files_analyzed: total_files,
+                            total_size,
+                            folders_analyzed: total_folders,
+                            current_path: dir_path.to_string_lossy().to_string(),
+                            progress_percentage,
+                            estimated_total_size,
+                        };
+
+                        context.sink.emit_progress(progress);
+                        state.last_emit_percentage = progress_percentage;
+                        state.files_processed_since_emit = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    if !current_folder_histograms.is_empty() {
+        state.histograms.insert(dir_path.to_path_buf(), current_folder_histograms);
+    }
+
+    // Chaque dossier est enregistré, même sans entrée : c'est ainsi que les dossiers vides sont repérés
+    state.folders.push(ScannedFolder {
+        name: dir_path.file_name()
+            .unwrap_or(dir_path.as_os_str())
+            .to_string_lossy()
+            .to_string(),
+        path: dir_path.to_path_buf(),
+        size: current_folder_size,
+        file_count: current_folder_file_count,
+        entry_count: current_folder_entry_count,
+    });
+
+    Ok(())
+}
+
+// Cache pour les types de fichiers
+static FILE_TYPE_CACHE: std::sync::OnceLock<HashMap<String, String>> = std::sync::OnceLock::new();
+
+pub fn get_file_type_cached(extension: &str) -> String {
+    let cache = FILE_TYPE_CACHE.get_or_init(|| {
+        let mut map = HashMap::new();
+        // Video
+        for ext in ["mp4", "avi", "mkv", "mov", "wmv", "flv", "webm", "m4v"] {
+            map.insert(ext.to_string(), "Video Files".to_string());
+        }
+        // Images
+        for ext in ["jpg", "jpeg", "png", "gif", "bmp", "tiff", "svg", "webp", "raw", "psd"] {
+            map.insert(ext.to_string(), "Images".to_string());
+        }
+        // Documents
+        for ext in ["pdf", "doc", "docx", "txt", "rtf", "odt", "pages"] {
+            map.insert(ext.to_string(), "Documents".to_string());
+        }
+        // Archives
+        for ext in ["zip", "rar", "7z", "tar", "gz", "bz2", "xz"] {
+            map.insert(ext.to_string(), "Archives".to_string());
+        }
+        // Audio
+        for ext in ["mp3", "wav", "flac", "aac", "ogg", "m4a", "wma"] {
+            map.insert(ext.to_string(), "Audio Files".to_string());
+        }
+        // Applications
+        for ext in ["exe", "app", "deb", "rpm", "dmg", "msi"] {
+            map.insert(ext.to_string(), "Applications".to_string());
+        }
+        map
+    });
+
+    cache.get(extension).cloned().unwrap_or_else(|| "Other".to_string())
+}
+
+pub fn estimate_total_size_fast(path: &Path) -> u64 {
+    use std::collections::VecDeque;
+
+    let mut queue = VecDeque::new();
+    queue.push_back(path.to_path_buf());
+
+    let mut total_size = 0u64;
+    let mut dirs_sampled = 0;
+    const MAX_DIRS_TO_SAMPLE: usize = 20;
+
+    while let Some(current_path) = queue.pop_front() {
+        if dirs_sampled >= MAX_DIRS_TO_SAMPLE {
+            break;
+        }
+
+        if let Ok(entries) = fs::read_dir(&current_path) {
+            let mut dir_size = 0u64;
+            let mut file_count = 0;
+
+            for entry in entries.flatten().take(50) { // Échantillonner seulement 50 entrées
+                let entry_path = entry.path();
+                if entry_path.is_file() {
+                    if let Ok(metadata) = entry.metadata() {
+                        dir_size += metadata.len();
+                        file_count += 1;
+                    }
+                } else if entry_path.is_dir() && queue.len() < 100 {
+                    queue.push_back(entry_path);
+                }
+            }
+
+            // Extrapoler la taille basée sur l'échantillon
+            if file_count > 10 {
+                total_size += dir_size * 3; // Facteur d'extrapolation
+            } else {
+                total_size += dir_size;
+            }
+
+            dirs_sampled += 1;
+        }
+    }
+
+    total_size.max(1_000_000) // Au moins 1MB
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Arborescence jetable propre à chaque test
+    fn temp_tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("diskvader-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub/deep")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("a.txt"), vec![0u8; 10]).unwrap();
+        fs::write(root.join("sub/b.bin"), vec![0u8; 200]).unwrap();
+        fs::write(root.join("sub/deep/c.txt"), vec![0u8; 30]).unwrap();
+        fs::write(root.join("node_modules/x.js"), vec![0u8; 150_000]).unwrap();
+        fs::write(root.join("skip.log"), vec![0u8; 500]).unwrap();
+        root
+    }
+
+    fn scan(root: &Path, options: &ScanOptions) -> ScanResults {
+        let shared: SharedScanResults = Arc::new(Mutex::new(ScanResults::default()));
+        run_scan(root.to_str().unwrap(), options, &shared, Arc::new(NoopProgressSink)).unwrap();
+        let results = shared.lock().unwrap().clone();
+        results
+    }
+
+    fn folder<'a>(results: &'a ScanResults, path: &Path) -> &'a ScannedFolder {
+        results.all_folders.iter().find(|folder| folder.path == path).unwrap()
+    }
+
+    fn scanned(path: &str, size: u64, file_count: u32) -> ScannedFolder {
+        ScannedFolder {
+            name: String::new(),
+            path: PathBuf::from(path),
+            size,
+            file_count,
+            entry_count: file_count,
+        }
+    }
+
+    #[test]
+    fn run_scan_totals_files_and_folders() {
+        let root = temp_tree("scan");
+        let results = scan(&root, &ScanOptions::default());
+
+        assert_eq!(results.total_files, 5);
+        assert_eq!(results.total_size, 150_740);
+        // Seuls les fichiers au-dessus du seuil sont conservés individuellement
+        let largest: Vec<&Path> = results.largest_files.iter().map(|file| file.path.as_path()).collect();
+        assert_eq!(largest, [root.join("node_modules/x.js")]);
+
+        let top = folder(&results, &root);
+        assert_eq!((top.size, top.file_count), (150_740, 5));
+        // 4 entrées directes, 2 dans sub, 1 dans sub/deep et 1 dans node_modules
+        assert_eq!(top.entry_count, 8);
+        assert_eq!(folder(&results, &root.join("sub")).size, 230);
+        assert_eq!(folder(&results, &root.join("sub/deep")).file_count, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn run_scan_records_empty_folders() {
+        let root = temp_tree("empty");
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::create_dir_all(root.join("nested/inner")).unwrap();
+        fs::create_dir_all(root.join("blank")).unwrap();
+        fs::write(root.join("blank/zero.txt"), b"").unwrap();
+        let results = scan(&root, &ScanOptions::default());
+
+        assert_eq!(folder(&results, &root.join("empty")).entry_count, 0);
+        assert_eq!(folder(&results, &root.join("nested/inner")).entry_count, 0);
+        // Un dossier qui ne contient qu'un dossier vide, ou qu'un fichier vide, n'est pas vide
+        assert_eq!(folder(&results, &root.join("nested")).entry_count, 1);
+        assert_eq!(folder(&results, &root.join("blank")).entry_count, 1);
+
+        let groups = crate::analyzers::cleanup_preview_groups(&results);
+        let empty = groups.iter().find(|group| group.cleanup_type == crate::analyzers::EMPTY_FOLDERS).unwrap();
+        let mut paths: Vec<&Path> = empty.items.iter().map(|item| item.path.as_path()).collect();
+        paths.sort();
+        assert_eq!(paths, [root.join("empty"), root.join("nested/inner")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn run_scan_skips_excluded_entries() {
+        let root = temp_tree("exclude");
+        let options = ScanOptions {
+            exclude_patterns: vec!["node_modules".to_string(), "skip.log".to_string()],
+            min_file_size: 0,
+        };
+        let results = scan(&root, &options);
+
+        assert_eq!(results.total_files, 3);
+        assert_eq!(results.total_size, 240);
+        assert!(results.all_folders.iter().all(|folder| !folder.path.starts_with(root.join("node_modules"))));
+        assert!(results.excluded_paths.contains(&root.join("node_modules")));
+        assert!(results.excluded_paths.contains(&root.join("skip.log")));
+        // Les entrées exclues ne comptent pas : a.txt et sub, puis 2 dans sub et 1 dans sub/deep
+        assert_eq!(folder(&results, &root).entry_count, 5);
+        let mut largest: Vec<&Path> = results.largest_files.iter().map(|file| file.path.as_path()).collect();
+        largest.sort();
+        assert_eq!(largest, [root.join("a.txt"), root.join("sub/b.bin"), root.join("sub/deep/c.txt")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn run_scan_rejects_missing_path() {
+        let shared: SharedScanResults = Arc::new(Mutex::new(ScanResults::default()));
+        let missing = std::env::temp_dir().join("diskvader-missing-folder");
+        let result = run_scan(missing.to_str().unwrap(), &ScanOptions::default(), &shared, Arc::new(NoopProgressSink));
+        assert!(matches!(result, Err(CommandError::PathDoesNotExist(_))));
+    }
+
+    #[test]
+    fn recursive_folder_data_sums_descendants() {
+        let folders = calculate_recursive_folder_data(vec![
+            scanned("/r", 10, 1),
+            scanned("/r/a", 0, 0),
+            scanned("/r/a/b", 5, 1),
+            scanned("/r/c", 7, 2),
+        ]);
+
+        let totals: Vec<(&Path, u64, u32)> = folders.iter()
+            .map(|folder| (folder.path.as_path(), folder.size, folder.file_count))
+            .collect();
+        assert_eq!(totals, [
+            (Path::new("/r"), 22, 4),
+            (Path::new("/r/a"), 5, 1),
+            (Path::new("/r/a/b"), 5, 1),
+            (Path::new("/r/c"), 7, 2),
+        ]);
+        assert_eq!(folders[1].name, "a");
+    }
+
+    #[test]
+    fn recursive_folder_data_creates_intermediate_folders() {
+        // /r/a n'a aucun fichier direct : il n'est pas relevé mais doit porter la taille de /r/a/b
+        let folders = calculate_recursive_folder_data(vec![
+            scanned("/r", 10, 1),
+            scanned("/r/a/b", 5, 1),
+            scanned("/r/c", 7, 2),
+        ]);
+
+        let totals: Vec<(&Path, u64, u32)> = folders.iter()
+            .map(|folder| (folder.path.as_path(), folder.size, folder.file_count))
+            .collect();
+        assert_eq!(totals, [
+            (Path::new("/r"), 22, 4),
+            (Path::new("/r/a"), 5, 1),
+            (Path::new("/r/a/b"), 5, 1),
+            (Path::new("/r/c"), 7, 2),
+        ]);
+    }
+
+    #[test]
+    fn recursive_folder_data_of_nothing_is_empty() {
+        assert!(calculate_recursive_folder_data(Vec::new()).is_empty());
+    }
+}
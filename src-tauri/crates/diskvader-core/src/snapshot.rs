@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::histograms::FileHistograms;
+use crate::models::{CommandError, ScannedFile, ScannedFolder, ScanResults};
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+/// Copie sérialisable d'un scan terminé, échangée entre la CLI et l'application.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanSnapshot {
+    pub format_version: u32,
+    pub created_at: String,
+    pub scan_path: String,
+    pub total_files: u32,
+    pub total_folders: u32,
+    pub total_size: u64,
+    pub scan_time: f32,
+    #[serde(default)]
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub largest_files: Vec<ScannedFile>,
+    pub folders: Vec<ScannedFolder>,
+    pub file_type_distribution: HashMap<String, (u64, u32)>,
+    /// Histogrammes d'âge et de taille par dossier, absents des instantanés de la version 1
+    #[serde(default)]
+    pub histograms: HashMap<PathBuf, HashMap<String, FileHistograms>>,
+}
+
+impl ScanSnapshot {
+    pub fn from_results(results: &ScanResults) -> Self {
+        ScanSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            scan_path: results.scan_path.clone(),
+            total_files: results.total_files,
+            total_folders: results.total_folders,
+            total_size: results.total_size,
+            scan_time: results.scan_time,
+            scanned_at: results.scanned_at,
+            largest_files: results.largest_files.clone(),
+            folders: results.all_folders.clone(),
+            file_type_distribution: results.file_type_distribution.clone(),
+            histograms: results.histograms.clone(),
+        }
+    }
+
+    pub fn into_results(self) -> ScanResults {
+        ScanResults {
+            total_files: self.total_files,
+            total_folders: self.total_folders,
+            total_size: self.total_size,
+            scan_time: self.scan_time,
+            scan_path: self.scan_path,
+            scanned_at: self.scanned_at,
+            largest_files: self.largest_files,
+            folders: self.folders.clone(),
+            all_folders: self.folders,
+            file_type_distribution: self.file_type_distribution,
+            histograms: self.histograms,
+            accounting: None,
+            ..ScanResults::default()
+        }
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), CommandError> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| CommandError::InternalError(format!("Failed to serialize snapshot: {}", e)))?;
+        fs::write(path, content)
+            .map_err(|e| CommandError::InternalError(format!("Failed to write snapshot {}: {}", path.display(), e)))
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<Self, CommandError> {
+        if !path.exists() {
+            return Err(CommandError::PathDoesNotExist(path.to_string_lossy().to_string()));
+        }
+
+        let content = fs::read_to_string(path)?;
+        let snapshot: ScanSnapshot = serde_json::from_str(&content)
+            .map_err(|e| CommandError::InternalError(format!("Invalid snapshot {}: {}", path.display(), e)))?;
+
+        if snapshot.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(CommandError::InternalError(format!(
+                "Snapshot format version {} is newer than the supported version {}",
+                snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            )));
+        }
+
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,283 @@
+use flate2::read::ZlibDecoder;
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::models::{CleanupCandidate, FileIdentity};
+use crate::trashbin::{path_from_bytes, percent_decode};
+use crate::volumes;
+
+pub const ORPHANED_THUMBNAILS: &str = "Orphaned Thumbnails";
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+const URI_KEYWORD: &[u8] = b"Thumb::URI";
+// Au-delà, le fichier n'est pas une miniature et n'est pas lu plus loin
+const MAX_CHUNK_LENGTH: u32 = 16 * 1024 * 1024;
+// Emplacements des supports amovibles : une source absente y est peut-être seulement démontée
+const REMOVABLE_ROOTS: [&str; 3] = ["/run/media", "/media", "/mnt"];
+
+/// Miniature dont le fichier source n'existe plus.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrphanedThumbnail {
+    pub path: PathBuf,
+    pub size: u64,
+    pub source_uri: String,
+    pub source_path: PathBuf,
+    #[serde(default)]
+    pub identity: Option<FileIdentity>,
+}
+
+/// Dossiers de miniatures FreeDesktop : `$XDG_CACHE_HOME/thumbnails` et l'ancien `~/.thumbnails`.
+pub fn thumbnail_directories() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| home.as_ref().map(|home| home.join(".cache")));
+    cache_home.map(|cache_home| cache_home.join("thumbnails")).into_iter()
+        .chain(home.map(|home| home.join(".thumbnails")))
+        .filter(|directory| directory.is_dir())
+        .collect()
+}
+
+/// Valeur de la clé `Thumb::URI` des blocs texte (tEXt, zTXt ou iTXt) d'un PNG.
+pub fn thumbnail_uri(path: &Path) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut signature = [0u8; 8];
+    reader.read_exact(&mut signature)?;
+    if &signature != PNG_SIGNATURE {
+        return Ok(None);
+    }
+
+    loop {
+        let mut header = [0u8; 8];
+        if reader.read_exact(&mut header).is_err() {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let chunk_type = &header[4..8];
+        if length > MAX_CHUNK_LENGTH || chunk_type == b"IEND" {
+            return Ok(None);
+        }
+
+        if !matches!(chunk_type, b"tEXt" | b"zTXt" | b"iTXt") {
+            // Données et CRC ignorés
+            io::copy(&mut (&mut reader).take(u64::from(length) + 4), &mut io::sink())?;
+            continue;
+        }
+        let mut data = vec![0u8; length as usize + 4];
+        reader.read_exact(&mut data)?;
+        data.truncate(length as usize);
+
+        let Some(separator) = data.iter().position(|byte| *byte == 0) else {
+            continue;
+        };
+        if &data[..separator] != URI_KEYWORD {
+            continue;
+        }
+        let value = &data[separator + 1..];
+        let text = match chunk_type {
+            b"tEXt" => value.to_vec(),
+            // Méthode de compression (1 octet), puis flux zlib
+            b"zTXt" => inflate(value.get(1..).unwrap_or_default())?,
+            _ => international_text(value)?,
+        };
+        return Ok(Some(String::from_utf8_lossy(&text).to_string()));
+    }
+}
+
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut text = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut text)?;
+    Ok(text)
+}
+
+// iTXt : drapeau et méthode de compression, langue et mot-clé traduit terminés par un zéro, puis le texte
+fn international_text(value: &[u8]) -> io::Result<Vec<u8>> {
+    let [compressed, _method, rest @ ..] = value else {
+        return Ok(Vec::new());
+    };
+    let mut fields = rest.splitn(3, |byte| *byte == 0);
+    let text = fields.nth(2).unwrap_or_default();
+    if *compressed == 1 {
+        inflate(text)
+    } else {
+        Ok(text.to_vec())
+    }
+}
+
+/// Chemin local désigné par une URI `file://`, `None` pour les autres schémas.
+pub fn local_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // `file://hôte/chemin` : seul l'hôte local (vide ou `localhost`) est pris en compte
+    let path = match rest.strip_prefix("localhost") {
+        Some(path) => path,
+        None => rest,
+    };
+    if !path.starts_with('/') {
+        return None;
+    }
+    Some(path_from_bytes(percent_decode(path)))
+}
+
+// Source sous un emplacement amovible dont aucun montage n'est actif : elle n'est pas considérée comme supprimée
+fn on_unmounted_media(source: &Path, mount_points: &[PathBuf]) -> bool {
+    let Some(root) = REMOVABLE_ROOTS.iter().map(Path::new).find(|root| source.starts_with(root)) else {
+        return false;
+    };
+    !volumes::longest_mount_point(source, mount_points.iter().map(PathBuf::as_path))
+        .is_some_and(|mount_point| mount_point.starts_with(root))
+}
+
+fn thumbnail_files(directory: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() && depth > 0 {
+            thumbnail_files(&path, depth - 1, files);
+        } else if file_type.is_file() && path.extension().is_some_and(|extension| extension == "png") {
+            files.push(path);
+        }
+    }
+}
+
+/// Miniatures dont la source locale a disparu ; les sources distantes, illisibles ou sur un support démonté
+/// sont conservées.
+pub fn orphaned_thumbnails() -> Vec<OrphanedThumbnail> {
+    let mount_points: Vec<PathBuf> = volumes::mounts().into_iter().map(|mount| mount.mount_point).collect();
+    let mut files = Vec::new();
+    for directory in thumbnail_directories() {
+        // `normal/`, `large/`... et `fail/<application>/`
+        thumbnail_files(&directory, 2, &mut files);
+    }
+
+    let mut orphans: Vec<OrphanedThumbnail> = files.into_iter()
+        .filter_map(|path| {
+            let source_uri = match thumbnail_uri(&path) {
+                Ok(Some(uri)) => uri,
+                Ok(None) => return None,
+                Err(e) => {
+                    log::debug!("Cannot read thumbnail {:?}: {}", path, e);
+                    return None;
+                }
+            };
+            let source_path = local_path(&source_uri)?;
+            if fs::symlink_metadata(&source_path).is_ok() || on_unmounted_media(&source_path, &mount_points) {
+                return None;
+            }
+            let identity = FileIdentity::of_path(&path).ok()?;
+            Some(OrphanedThumbnail {
+                size: identity.size,
+                path,
+                source_uri,
+                source_path,
+                identity: Some(identity),
+            })
+        })
+        .collect();
+
+    orphans.sort_by_key(|orphan| Reverse(orphan.size));
+    orphans
+}
+
+pub fn cleanup_candidates(orphans: &[OrphanedThumbnail]) -> Vec<CleanupCandidate> {
+    orphans.iter()
+        .map(|orphan| CleanupCandidate {
+            path: orphan.path.clone(),
+            size: orphan.size,
+            is_dir: false,
+            reason: format!("Thumbnail of deleted file {}", orphan.source_path.display()),
+            identity: orphan.identity.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn deflate(text: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(text).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    // PNG minimal : un bloc IHDR à ignorer, le bloc texte donné puis IEND ; les CRC ne sont pas vérifiés
+    fn write_png(name: &str, chunk_type: &[u8; 4], data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("diskvader-thumbnails-{}-{}.png", name, std::process::id()));
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (chunk_type, data) in [(b"IHDR", &[0u8; 13][..]), (chunk_type, data), (b"IEND", &[][..])] {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(chunk_type);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]);
+        }
+        fs::write(&path, png).unwrap();
+        path
+    }
+
+    fn read_uri(name: &str, chunk_type: &[u8; 4], value: &[u8]) -> Option<String> {
+        let data = [URI_KEYWORD, b"\0", value].concat();
+        let path = write_png(name, chunk_type, &data);
+        let uri = thumbnail_uri(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        uri
+    }
+
+    #[test]
+    fn thumbnail_uri_reads_plain_text() {
+        assert_eq!(read_uri("text", b"tEXt", b"file:///home/a.jpg").as_deref(), Some("file:///home/a.jpg"));
+    }
+
+    #[test]
+    fn thumbnail_uri_reads_compressed_text() {
+        let value = [&[0u8][..], &deflate(b"file:///home/b.jpg")].concat();
+        assert_eq!(read_uri("ztxt", b"zTXt", &value).as_deref(), Some("file:///home/b.jpg"));
+    }
+
+    #[test]
+    fn thumbnail_uri_reads_international_text() {
+        let plain = [&[0u8, 0][..], b"fr\0\0", b"file:///home/c.jpg"].concat();
+        assert_eq!(read_uri("itxt", b"iTXt", &plain).as_deref(), Some("file:///home/c.jpg"));
+        let compressed = [&[1u8, 0][..], b"\0\0", &deflate(b"file:///home/d.jpg")].concat();
+        assert_eq!(read_uri("itxt-compressed", b"iTXt", &compressed).as_deref(), Some("file:///home/d.jpg"));
+    }
+
+    #[test]
+    fn thumbnail_uri_ignores_other_keywords_and_files() {
+        let path = write_png("keyword", b"tEXt", b"Thumb::MTime\x001700000000");
+        assert_eq!(thumbnail_uri(&path).unwrap(), None);
+        fs::write(&path, b"not a png").unwrap();
+        assert_eq!(thumbnail_uri(&path).unwrap(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn local_path_accepts_only_the_local_host() {
+        assert_eq!(local_path("file:///home/user/a%20b.jpg"), Some(PathBuf::from("/home/user/a b.jpg")));
+        assert_eq!(local_path("file://localhost/tmp/c.jpg"), Some(PathBuf::from("/tmp/c.jpg")));
+        assert_eq!(local_path("file://server/share/c.jpg"), None);
+        assert_eq!(local_path("file://localhostile/c.jpg"), None);
+        assert_eq!(local_path("smb://server/share/c.jpg"), None);
+    }
+
+    #[test]
+    fn sources_on_unmounted_media_are_not_orphans() {
+        let mount_points = [PathBuf::from("/"), PathBuf::from("/run"), PathBuf::from("/media/user/USB")];
+        assert!(!on_unmounted_media(Path::new("/home/user/a.jpg"), &mount_points));
+        assert!(!on_unmounted_media(Path::new("/media/user/USB/a.jpg"), &mount_points));
+        assert!(on_unmounted_media(Path::new("/media/user/CARD/a.jpg"), &mount_points));
+        assert!(on_unmounted_media(Path::new("/run/media/user/USB/a.jpg"), &mount_points));
+        assert!(on_unmounted_media(Path::new("/mnt/backup/a.jpg"), &mount_points));
+    }
+}
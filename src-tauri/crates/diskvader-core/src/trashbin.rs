@@ -0,0 +1,661 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::cleanup::CleanupFailure;
+use crate::models::{entry_id, CommandError};
+use crate::protected::ProtectedPaths;
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))]
+use crate::volumes;
+
+// Format de `DeletionDate` dans les fichiers .trashinfo (heure locale)
+const DELETION_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Élément d'une corbeille FreeDesktop, décrit par son fichier `.trashinfo`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    pub original_path: PathBuf,
+    pub deletion_date: Option<NaiveDateTime>,
+    pub size: u64,
+    pub is_dir: bool,
+    pub trash_dir: PathBuf,
+}
+
+/// Occupation d'une corbeille ; `topdir` est absent pour la corbeille de l'utilisateur.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrashVolume {
+    pub topdir: Option<PathBuf>,
+    pub trash_dir: PathBuf,
+    pub size: u64,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TrashReport {
+    pub paths: Vec<PathBuf>,
+    pub freed_size: u64,
+    pub failures: Vec<CleanupFailure>,
+}
+
+/// Corbeille au format FreeDesktop : `files/`, `info/` et le cache `directorysizes`.
+/// `topdir` est la racine du volume pour une corbeille dont les chemins d'origine sont relatifs.
+#[derive(Clone, Debug)]
+pub struct TrashDirectory {
+    pub root: PathBuf,
+    pub topdir: Option<PathBuf>,
+}
+
+struct DirectorySize {
+    size: u64,
+    info_mtime: u64,
+    name: String,
+}
+
+impl TrashDirectory {
+    /// Corbeille de l'utilisateur : `$XDG_DATA_HOME/Trash`, à défaut `~/.local/share/Trash`.
+    pub fn home() -> Option<Self> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+        Some(TrashDirectory {
+            root: data_home.join("Trash"),
+            topdir: None,
+        })
+    }
+
+    /// Corbeilles d'un volume : `$topdir/.Trash/$uid` si `.Trash` est un vrai dossier avec le sticky bit,
+    /// et `$topdir/.Trash-$uid`.
+    #[cfg(unix)]
+    pub fn for_topdir(topdir: &Path, uid: u32) -> Vec<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut directories = Vec::new();
+        let shared = topdir.join(".Trash");
+        let shared_is_valid = fs::symlink_metadata(&shared)
+            .map(|metadata| metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0)
+            .unwrap_or(false);
+        if shared_is_valid {
+            directories.push(shared.join(uid.to_string()));
+        }
+        directories.push(topdir.join(format!(".Trash-{}", uid)));
+
+        directories.into_iter()
+            .filter(|root| fs::symlink_metadata(root).map(|metadata| metadata.is_dir()).unwrap_or(false))
+            .map(|root| TrashDirectory {
+                root,
+                topdir: Some(topdir.to_path_buf()),
+            })
+            .collect()
+    }
+
+    /// Taille et nombre d'éléments de la corbeille.
+    pub fn summary(&self) -> TrashVolume {
+        let items = self.items();
+        TrashVolume {
+            topdir: self.topdir.clone(),
+            trash_dir: self.root.clone(),
+            size: items.iter().map(|item| item.size).sum(),
+            count: items.len() as u32,
+        }
+    }
+
+    fn files_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info_dir(&self) -> PathBuf {
+        self.root.join("info")
+    }
+
+    fn info_path(&self, name: &str) -> PathBuf {
+        self.info_dir().join(format!("{}.trashinfo", name))
+    }
+
+    /// Éléments de la corbeille ; un `.trashinfo` sans fichier correspondant est ignoré.
+    pub fn items(&self) -> Vec<TrashItem> {
+        let Ok(entries) = fs::read_dir(self.info_dir()) else {
+            return Vec::new();
+        };
+        let mut cache = self.read_directory_sizes();
+        let mut cache_changed = false;
+        let mut items = Vec::new();
+
+        for entry in entries.flatten() {
+            let info_name = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = info_name.strip_suffix(".trashinfo") else {
+                continue;
+            };
+            let Ok(metadata) = fs::symlink_metadata(self.files_dir().join(name)) else {
+                continue;
+            };
+            let Some((original_path, deletion_date)) = fs::read_to_string(entry.path()).ok().and_then(|content| self.parse_info(&content)) else {
+                log::warn!("Ignoring unreadable trash info {:?}", entry.path());
+                continue;
+            };
+
+            let size = if metadata.is_dir() {
+                let info_mtime = modified_secs(&entry.path());
+                match cache.iter().find(|cached| cached.name == name && cached.info_mtime == info_mtime) {
+                    Some(cached) => cached.size,
+                    None => {
+                        let size = directory_size(&self.files_dir().join(name));
+                        cache.retain(|cached| cached.name != name);
+                        cache.push(DirectorySize { size, info_mtime, name: name.to_string() });
+                        cache_changed = true;
+                        size
+                    }
+                }
+            } else {
+                metadata.len()
+            };
+
+            items.push(TrashItem {
+                id: entry_id("t", &self.root.to_string_lossy(), Path::new(name)),
+                name: name.to_string(),
+                original_path,
+                deletion_date,
+                size,
+                is_dir: metadata.is_dir(),
+                trash_dir: self.root.clone(),
+            });
+        }
+
+        // Les dossiers qui ne sont plus dans la corbeille sortent du cache
+        let cached = cache.len();
+        cache.retain(|cached| items.iter().any(|item| item.is_dir && item.name == cached.name));
+        if cache_changed || cache.len() != cached {
+            self.write_directory_sizes(&cache);
+        }
+
+        items.sort_by_key(|item| Reverse(item.deletion_date));
+        items
+    }
+
+    fn parse_info(&self, content: &str) -> Option<(PathBuf, Option<NaiveDateTime>)> {
+        let mut in_section = false;
+        let mut path = None;
+        let mut deletion_date = None;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_section = line == "[Trash Info]";
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+            if let Some(value) = line.strip_prefix("Path=") {
+                path = Some(path_from_bytes(percent_decode(value)));
+            } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                deletion_date = NaiveDateTime::parse_from_str(value, DELETION_DATE_FORMAT).ok();
+            }
+        }
+
+        let path = path?;
+        // `..` ou un chemin absolu dans la corbeille d'un volume enverraient la restauration hors du volume
+        let path = match &self.topdir {
+            Some(topdir) => {
+                if path.as_os_str().is_empty() || !path.components().all(|component| matches!(component, Component::Normal(_))) {
+                    return None;
+                }
+                topdir.join(path)
+            }
+            None => {
+                if !path.is_absolute() || path.components().any(|component| component == Component::ParentDir) {
+                    return None;
+                }
+                path
+            }
+        };
+        Some((path, deletion_date))
+    }
+
+    /// Remet l'élément à son emplacement d'origine, sans écraser un fichier existant.
+    pub fn restore(&self, item: &TrashItem, protected: &ProtectedPaths) -> Result<(), String> {
+        if let Some(reason) = protected.violation(&item.original_path) {
+            return Err(format!("Protected path: {}", reason));
+        }
+        if fs::symlink_metadata(&item.original_path).is_ok() {
+            return Err("A file already exists at the original location".to_string());
+        }
+        if let Some(parent) = item.original_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::rename(self.files_dir().join(&item.name), &item.original_path).map_err(|e| e.to_string())?;
+        self.forget(item);
+        Ok(())
+    }
+
+    /// Supprime définitivement l'élément.
+    pub fn purge(&self, item: &TrashItem) -> Result<(), String> {
+        let path = self.files_dir().join(&item.name);
+        let result = if item.is_dir {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        self.forget(item);
+        Ok(())
+    }
+
+    // Retire le .trashinfo et l'entrée du cache une fois le fichier sorti de la corbeille
+    fn forget(&self, item: &TrashItem) {
+        if let Err(e) = fs::remove_file(self.info_path(&item.name)) {
+            log::warn!("Cannot remove trash info for {:?}: {}", item.name, e);
+        }
+        if item.is_dir {
+            let mut cache = self.read_directory_sizes();
+            let cached = cache.len();
+            cache.retain(|cached| cached.name != item.name);
+            if cache.len() != cached {
+                self.write_directory_sizes(&cache);
+            }
+        }
+    }
+
+    /// Vide la corbeille, y compris les fichiers orphelins sans `.trashinfo`.
+    pub fn empty(&self) -> TrashReport {
+        let mut report = TrashReport::default();
+        for item in self.items() {
+            match self.purge(&item) {
+                Ok(()) => {
+                    report.freed_size += item.size;
+                    report.paths.push(item.original_path);
+                }
+                Err(error) => report.failures.push(CleanupFailure::new(&item.original_path, error)),
+            }
+        }
+
+        for directory in [self.files_dir(), self.info_dir()] {
+            let Ok(entries) = fs::read_dir(&directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let result = if path.is_dir() && !path.is_symlink() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                if let Err(e) = result {
+                    report.failures.push(CleanupFailure::new(&path, e.to_string()));
+                }
+            }
+        }
+        let _ = fs::remove_file(self.root.join("directorysizes"));
+        report
+    }
+
+    fn read_directory_sizes(&self) -> Vec<DirectorySize> {
+        let Ok(content) = fs::read_to_string(self.root.join("directorysizes")) else {
+            return Vec::new();
+        };
+        content.lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let size = fields.next()?.parse().ok()?;
+                let info_mtime = fields.next()?.parse().ok()?;
+                let name = String::from_utf8_lossy(&percent_decode(fields.next()?)).to_string();
+                Some(DirectorySize { size, info_mtime, name })
+            })
+            .collect()
+    }
+
+    // Réécriture atomique du cache, comme le demande la spécification
+    fn write_directory_sizes(&self, cache: &[DirectorySize]) {
+        let content: String = cache.iter()
+            .map(|cached| format!("{} {} {}\n", cached.size, cached.info_mtime, percent_encode(cached.name.as_bytes())))
+            .collect();
+        let path = self.root.join("directorysizes");
+        let partial = self.root.join(format!("directorysizes.{}", std::process::id()));
+        let written = fs::write(&partial, content).and_then(|()| fs::rename(&partial, &path));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&partial);
+            log::warn!("Cannot update {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Corbeilles consultées par le navigateur de corbeille : celle de l'utilisateur puis celles de chaque volume monté.
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))]
+pub fn trash_directories() -> Vec<TrashDirectory> {
+    // SAFETY: getuid ne peut pas échouer
+    let uid = unsafe { libc::getuid() };
+    let mut directories: Vec<TrashDirectory> = TrashDirectory::home().into_iter().collect();
+    for mount in volumes::mounts().iter().filter(|mount| !mount.is_pseudo()) {
+        for directory in TrashDirectory::for_topdir(&mount.mount_point, uid) {
+            if !directories.iter().any(|known| known.root == directory.root) {
+                directories.push(directory);
+            }
+        }
+    }
+    directories
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+pub fn trash_directories() -> Vec<TrashDirectory> {
+    Vec::new()
+}
+
+pub fn list_items() -> Vec<TrashItem> {
+    trash_directories().iter().flat_map(TrashDirectory::items).collect()
+}
+
+fn find_items(ids: &[String]) -> (Vec<(TrashDirectory, TrashItem)>, Vec<CleanupFailure>) {
+    let mut found = Vec::new();
+    for directory in trash_directories() {
+        for item in directory.items() {
+            if ids.contains(&item.id) {
+                found.push((directory.clone(), item));
+            }
+        }
+    }
+    let failures = ids.iter()
+        .filter(|id| !found.iter().any(|(_, item)| &&item.id == id))
+        .map(|id| CleanupFailure::new(Path::new(id), "Item is no longer in the trash"))
+        .collect();
+    (found, failures)
+}
+
+pub fn restore_items(ids: &[String], protected: &ProtectedPaths) -> TrashReport {
+    let (found, failures) = find_items(ids);
+    let mut report = TrashReport { failures, ..TrashReport::default() };
+    for (directory, item) in found {
+        match directory.restore(&item, protected) {
+            Ok(()) => {
+                log::info!("Restored {:?} from the trash", item.original_path);
+                report.paths.push(item.original_path);
+            }
+            Err(error) => report.failures.push(CleanupFailure::new(&item.original_path, error)),
+        }
+    }
+    report
+}
+
+fn purge(found: Vec<(TrashDirectory, TrashItem)>, mut report: TrashReport) -> TrashReport {
+    for (directory, item) in found {
+        match directory.purge(&item) {
+            Ok(()) => {
+                report.freed_size += item.size;
+                report.paths.push(item.original_path);
+            }
+            Err(error) => report.failures.push(CleanupFailure::new(&item.original_path, error)),
+        }
+    }
+    report
+}
+
+pub fn purge_items(ids: &[String]) -> TrashReport {
+    let (found, failures) = find_items(ids);
+    purge(found, TrashReport { failures, ..TrashReport::default() })
+}
+
+/// Corbeilles retenues : celle dont la racine est `trash_dir`, toutes avec `all_volumes`,
+/// sinon seulement celle de l'utilisateur.
+fn selected_directories(trash_dir: Option<&Path>, all_volumes: bool) -> Result<Vec<TrashDirectory>, CommandError> {
+    let directories: Vec<TrashDirectory> = trash_directories().into_iter()
+        .filter(|directory| match trash_dir {
+            Some(trash_dir) => directory.root == trash_dir,
+            None => all_volumes || directory.topdir.is_none(),
+        })
+        .collect();
+    if directories.is_empty() {
+        return Err(CommandError::InternalError(match trash_dir {
+            Some(trash_dir) => format!("{} is not a known trash directory", trash_dir.display()),
+            None => "No trash directory found".to_string(),
+        }));
+    }
+    Ok(directories)
+}
+
+pub fn volumes_summary() -> Vec<TrashVolume> {
+    trash_directories().iter().map(TrashDirectory::summary).collect()
+}
+
+/// Supprime définitivement les éléments mis à la corbeille il y a plus de `days` jours.
+pub fn purge_older_than(days: u32, trash_dir: Option<&Path>, all_volumes: bool) -> Result<TrashReport, CommandError> {
+    let limit = Local::now().naive_local() - Duration::days(i64::from(days));
+    let found = selected_directories(trash_dir, all_volumes)?.into_iter()
+        .flat_map(|directory| {
+            directory.items().into_iter()
+                .filter(|item| item.deletion_date.is_some_and(|date| date < limit))
+                .map(move |item| (directory.clone(), item))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    Ok(purge(found, TrashReport::default()))
+}
+
+/// Vide la corbeille de l'utilisateur, celle de `trash_dir` ou, avec `all_volumes`, toutes les corbeilles.
+pub fn empty(trash_dir: Option<&Path>, all_volumes: bool) -> Result<TrashReport, CommandError> {
+    let mut report = TrashReport::default();
+    for directory in selected_directories(trash_dir, all_volumes)? {
+        let emptied = directory.empty();
+        report.paths.extend(emptied.paths);
+        report.freed_size += emptied.freed_size;
+        report.failures.extend(emptied.failures);
+    }
+    Ok(report)
+}
+
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries.flatten()
+        .filter_map(|entry| entry.metadata().ok().map(|metadata| (entry, metadata)))
+        .map(|(entry, metadata)| if metadata.is_dir() { directory_size(&entry.path()) } else { metadata.len() })
+        .sum()
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+pub(crate) fn percent_decode(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(OsString::from(String::from_utf8_lossy(&bytes).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Corbeille jetable propre à chaque test
+    fn temp_trash(name: &str) -> TrashDirectory {
+        let root = std::env::temp_dir().join(format!("diskvader-trash-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("files")).unwrap();
+        fs::create_dir_all(root.join("info")).unwrap();
+        TrashDirectory { root, topdir: None }
+    }
+
+    fn trash(directory: &TrashDirectory, name: &str, original: &str, content: Option<&[u8]>) {
+        match content {
+            Some(content) => fs::write(directory.files_dir().join(name), content).unwrap(),
+            None => {
+                fs::create_dir_all(directory.files_dir().join(name).join("inner")).unwrap();
+                fs::write(directory.files_dir().join(name).join("inner/data"), vec![0u8; 300]).unwrap();
+            }
+        }
+        let info = format!("[Trash Info]\nPath={}\nDeletionDate=2024-03-01T10:00:00\n", original);
+        fs::write(directory.info_path(name), info).unwrap();
+    }
+
+    #[test]
+    fn parse_info_decodes_path_and_date() {
+        let home = TrashDirectory { root: PathBuf::from("/home/me/.local/share/Trash"), topdir: None };
+        let (path, date) = home.parse_info("[Trash Info]\nPath=/home/me/My%20File%25.txt\nDeletionDate=2024-03-01T10:20:30\n").unwrap();
+        assert_eq!(path, PathBuf::from("/home/me/My File%.txt"));
+        assert_eq!(date, NaiveDateTime::parse_from_str("2024-03-01T10:20:30", DELETION_DATE_FORMAT).ok());
+    }
+
+    #[test]
+    fn parse_info_resolves_relative_paths_on_volumes() {
+        let volume = TrashDirectory { root: PathBuf::from("/mnt/usb/.Trash-1000"), topdir: Some(PathBuf::from("/mnt/usb")) };
+        let (path, date) = volume.parse_info("[Trash Info]\nPath=photos/a.jpg\nDeletionDate=not a date\n").unwrap();
+        assert_eq!(path, PathBuf::from("/mnt/usb/photos/a.jpg"));
+        assert_eq!(date, None);
+    }
+
+    #[test]
+    fn parse_info_rejects_paths_leaving_the_volume() {
+        let volume = TrashDirectory { root: PathBuf::from("/mnt/usb/.Trash-1000"), topdir: Some(PathBuf::from("/mnt/usb")) };
+        assert!(volume.parse_info("[Trash Info]\nPath=../../home/me/.bashrc\n").is_none());
+        assert!(volume.parse_info("[Trash Info]\nPath=photos/%2E%2E/%2E%2E/etc/cron.d/job\n").is_none());
+        assert!(volume.parse_info("[Trash Info]\nPath=/home/me/.bashrc\n").is_none());
+        assert!(volume.parse_info("[Trash Info]\nPath=./photos/a.jpg\n").is_none());
+        assert!(volume.parse_info("[Trash Info]\nPath=\n").is_none());
+    }
+
+    #[test]
+    fn parse_info_rejects_parent_components_in_the_home_trash() {
+        let home = TrashDirectory { root: PathBuf::from("/home/me/.local/share/Trash"), topdir: None };
+        assert!(home.parse_info("[Trash Info]\nPath=/home/me/../../etc/passwd\n").is_none());
+        assert!(home.parse_info("[Trash Info]\nPath=relative/a.txt\n").is_none());
+    }
+
+    #[test]
+    fn restore_refuses_protected_locations() {
+        let directory = temp_trash("restore-protected");
+        let original = directory.root.join("restored/a.txt");
+        trash(&directory, "a.txt", &original.to_string_lossy(), Some(b"hello"));
+        let item = directory.items().pop().unwrap();
+
+        let mut protected = ProtectedPaths::default();
+        protected.protect(directory.root.join("restored"));
+        assert!(directory.restore(&item, &protected).unwrap_err().starts_with("Protected path"));
+        assert!(directory.files_dir().join("a.txt").exists());
+
+        directory.restore(&item, &ProtectedPaths::default()).unwrap();
+        assert_eq!(fs::read(&original).unwrap(), b"hello");
+        assert!(!directory.info_path("a.txt").exists());
+        fs::remove_dir_all(&directory.root).unwrap();
+    }
+
+    #[test]
+    fn parse_info_reads_only_the_trash_info_section() {
+        let home = TrashDirectory { root: PathBuf::from("/trash"), topdir: None };
+        assert!(home.parse_info("[Other]\nPath=/etc/passwd\n").is_none());
+        assert!(home.parse_info("[Trash Info]\nDeletionDate=2024-03-01T10:00:00\n").is_none());
+    }
+
+    #[test]
+    fn percent_encoding_round_trips() {
+        let name = "dossier été/a b%.txt";
+        let encoded = percent_encode(name.as_bytes());
+        assert_eq!(encoded, "dossier%20%C3%A9t%C3%A9/a%20b%25.txt");
+        assert_eq!(percent_decode(&encoded), name.as_bytes());
+        // Un % isolé est gardé tel quel
+        assert_eq!(percent_decode("100%"), b"100%");
+    }
+
+    #[test]
+    fn items_measure_directories_through_the_size_cache() {
+        let directory = temp_trash("items");
+        trash(&directory, "a.txt", "/home/me/a.txt", Some(b"hello"));
+        trash(&directory, "folder", "/home/me/folder", None);
+        fs::write(directory.info_path("orphan"), "[Trash Info]\nPath=/home/me/orphan\n").unwrap();
+
+        let mut items = directory.items();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        let summary: Vec<(&str, u64, bool)> = items.iter().map(|item| (item.name.as_str(), item.size, item.is_dir)).collect();
+        assert_eq!(summary, [("a.txt", 5, false), ("folder", 300, true)]);
+
+        let cache = directory.read_directory_sizes();
+        assert_eq!(cache.len(), 1);
+        assert_eq!((cache[0].name.as_str(), cache[0].size), ("folder", 300));
+
+        // Tant que le .trashinfo n'a pas changé, la taille vient du cache
+        let stale: Vec<DirectorySize> = cache.into_iter().map(|cached| DirectorySize { size: 42, ..cached }).collect();
+        directory.write_directory_sizes(&stale);
+        let folder = directory.items().into_iter().find(|item| item.is_dir).unwrap();
+        assert_eq!(folder.size, 42);
+        fs::remove_dir_all(&directory.root).unwrap();
+    }
+
+    #[test]
+    fn purge_forgets_the_item() {
+        let directory = temp_trash("purge");
+        trash(&directory, "folder", "/home/me/folder", None);
+        let item = directory.items().pop().unwrap();
+
+        directory.purge(&item).unwrap();
+        assert!(!directory.files_dir().join("folder").exists());
+        assert!(!directory.info_path("folder").exists());
+        assert!(directory.read_directory_sizes().is_empty());
+        fs::remove_dir_all(&directory.root).unwrap();
+    }
+
+    #[test]
+    fn empty_removes_items_and_orphans() {
+        let directory = temp_trash("empty");
+        trash(&directory, "a.txt", "/home/me/a.txt", Some(b"hello"));
+        trash(&directory, "folder", "/home/me/folder", None);
+        fs::write(directory.files_dir().join("no-info"), b"x").unwrap();
+
+        let report = directory.empty();
+        assert_eq!(report.freed_size, 305);
+        assert_eq!(report.paths.len(), 2);
+        assert!(report.failures.is_empty());
+        assert_eq!(fs::read_dir(directory.files_dir()).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(directory.info_dir()).unwrap().count(), 0);
+        assert!(!directory.root.join("directorysizes").exists());
+        fs::remove_dir_all(&directory.root).unwrap();
+    }
+}
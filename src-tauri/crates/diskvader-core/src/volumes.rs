@@ -0,0 +1,332 @@
+use serde::{Serialize, Deserialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Systèmes de fichiers virtuels : ni données utilisateur ni corbeille
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "pstore",
+    "debugfs", "tracefs", "mqueue", "hugetlbfs", "configfs", "fusectl", "bpf", "autofs",
+    "binfmt_misc", "efivarfs", "nsfs", "rpc_pipefs",
+];
+
+/// Entrée de la table des montages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountEntry {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl MountEntry {
+    pub fn is_pseudo(&self) -> bool {
+        PSEUDO_FILESYSTEMS.contains(&self.fs_type.as_str())
+    }
+}
+
+/// Occupation d'un système de fichiers selon `statvfs`, en octets.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct FilesystemUsage {
+    pub total: u64,
+    /// Blocs occupés : total moins blocs libres, réserve exclue
+    pub used: u64,
+    pub free: u64,
+    /// Espace libre accessible à un utilisateur non privilégié
+    pub available: u64,
+    /// Blocs libres réservés à root
+    pub reserved: u64,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    /// Inodes libres accessibles à un utilisateur non privilégié
+    pub inodes_free: u64,
+}
+
+impl FilesystemUsage {
+    /// Pourcentage d'occupation calculé comme `df` : la réserve ne compte ni comme utilisée ni comme disponible.
+    pub fn used_percentage(&self) -> f32 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            return 0.0;
+        }
+        self.used as f32 / usable as f32 * 100.0
+    }
+}
+
+#[cfg(unix)]
+pub fn filesystem_usage(path: &Path) -> io::Result<FilesystemUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let fragment = stats.f_frsize as u64;
+    let total = stats.f_blocks as u64 * fragment;
+    let free = stats.f_bfree as u64 * fragment;
+    let available = stats.f_bavail as u64 * fragment;
+    Ok(FilesystemUsage {
+        total,
+        used: total.saturating_sub(free),
+        free,
+        available,
+        reserved: free.saturating_sub(available),
+        inodes_total: stats.f_files as u64,
+        inodes_used: (stats.f_files as u64).saturating_sub(stats.f_ffree as u64),
+        inodes_free: stats.f_favail as u64,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn filesystem_usage(_path: &Path) -> io::Result<FilesystemUsage> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "statvfs is not available on this platform"))
+}
+
+/// Périphérique du système de fichiers qui contient `path`.
+#[cfg(unix)]
+pub fn device_of(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_of(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Place occupée sur le disque : blocs alloués, inférieure à la taille apparente pour les fichiers creux.
+#[cfg(unix)]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Volume monté et son occupation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Volume {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub usage: FilesystemUsage,
+    pub removable: bool,
+    pub read_only: bool,
+}
+
+/// Volumes réels (pseudo-systèmes et volumes vides écartés), un par point de montage.
+pub fn list_volumes() -> Vec<Volume> {
+    let mut volumes: Vec<Volume> = Vec::new();
+    for mount in mounts().into_iter().filter(|mount| !mount.is_pseudo()) {
+        let usage = match filesystem_usage(&mount.mount_point) {
+            Ok(usage) if usage.total > 0 => usage,
+            Ok(_) => continue,
+            Err(e) => {
+                log::debug!("Cannot stat {:?}: {}", mount.mount_point, e);
+                continue;
+            }
+        };
+        // Un montage ultérieur au même endroit masque le précédent
+        volumes.retain(|volume| volume.mount_point != mount.mount_point);
+        volumes.push(Volume {
+            removable: is_removable(&mount.device),
+            device: mount.device,
+            mount_point: mount.mount_point,
+            fs_type: mount.fs_type,
+            usage,
+            read_only: mount.read_only,
+        });
+    }
+    volumes.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    volumes
+}
+
+/// Point de montage le plus long contenant `path` : `/home` l'emporte sur `/` pour `/home/user`.
+pub fn longest_mount_point<'a>(path: &Path, mount_points: impl IntoIterator<Item = &'a Path>) -> Option<&'a Path> {
+    mount_points.into_iter()
+        .filter(|mount_point| path.starts_with(mount_point))
+        .max_by_key(|mount_point| mount_point.components().count())
+}
+
+/// Volume de `volumes` qui contient `path`, liens symboliques résolus.
+pub fn volume_for<'a>(path: &Path, volumes: &'a [Volume]) -> Option<&'a Volume> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mount_point = longest_mount_point(&path, volumes.iter().map(|volume| volume.mount_point.as_path()))?;
+    volumes.iter().find(|volume| volume.mount_point == mount_point)
+}
+
+/// Volume qui contient `path`, sans interroger les autres montages.
+pub fn volume_containing(path: &Path) -> Option<Volume> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let candidates: Vec<MountEntry> = mounts().into_iter().filter(|mount| !mount.is_pseudo()).collect();
+    let mount_point = longest_mount_point(&path, candidates.iter().map(|mount| mount.mount_point.as_path()))?.to_path_buf();
+    // Un montage ultérieur au même endroit masque le précédent
+    let mount = candidates.into_iter().rev().find(|mount| mount.mount_point == mount_point)?;
+    let usage = filesystem_usage(&mount.mount_point)
+        .map_err(|e| log::debug!("Cannot stat {:?}: {}", mount.mount_point, e))
+        .ok()?;
+    Some(Volume {
+        removable: is_removable(&mount.device),
+        device: mount.device,
+        mount_point: mount.mount_point,
+        fs_type: mount.fs_type,
+        usage,
+        read_only: mount.read_only,
+    })
+}
+
+// Drapeau `removable` du disque dans sysfs ; une partition hérite de celui de son disque
+#[cfg(target_os = "linux")]
+fn is_removable(device: &str) -> bool {
+    let Some(name) = std::fs::canonicalize(device).ok().and_then(|device| device.file_name().map(|name| name.to_owned())) else {
+        return false;
+    };
+    let block = Path::new("/sys/class/block").join(name);
+    let disk = if block.join("partition").exists() {
+        std::fs::canonicalize(&block).ok().and_then(|block| block.parent().map(Path::to_path_buf))
+    } else {
+        Some(block)
+    };
+    disk.and_then(|disk| std::fs::read_to_string(disk.join("removable")).ok())
+        .is_some_and(|removable| removable.trim() == "1")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_removable(_device: &str) -> bool {
+    false
+}
+
+/// Volumes montés, tels que décrits par `/proc/self/mounts`.
+#[cfg(target_os = "linux")]
+pub fn mounts() -> Vec<MountEntry> {
+    match std::fs::read_to_string("/proc/self/mounts") {
+        Ok(content) => parse_mounts(&content),
+        Err(e) => {
+            log::warn!("Cannot read the mount table: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mounts() -> Vec<MountEntry> {
+    Vec::new()
+}
+
+pub fn parse_mounts(content: &str) -> Vec<MountEntry> {
+    content.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape(fields.next()?);
+            let mount_point = PathBuf::from(unescape(fields.next()?));
+            let fs_type = fields.next()?.to_string();
+            let read_only = fields.next().is_some_and(|options| options.split(',').any(|option| option == "ro"));
+            Some(MountEntry { device, mount_point, fs_type, read_only })
+        })
+        .collect()
+}
+
+// Les espaces et caractères spéciaux sont écrits en octal (`\040` pour une espace)
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let octal = (bytes[index] == b'\\')
+            .then(|| field.get(index + 1..index + 4))
+            .flatten()
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match octal {
+            Some(byte) => {
+                unescaped.push(byte);
+                index += 4;
+            }
+            None => {
+                unescaped.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/sda2 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+/dev/sda3 /home ext4 rw,relatime 0 0
+/dev/sdb1 /media/me/USB\\040Stick vfat ro,nosuid,nodev 0 0
+incomplete
+";
+
+    fn volume(mount_point: &str) -> Volume {
+        Volume {
+            device: String::new(),
+            mount_point: PathBuf::from(mount_point),
+            fs_type: "ext4".to_string(),
+            usage: FilesystemUsage::default(),
+            removable: false,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn parse_mounts_reads_fields_and_options() {
+        let mounts = parse_mounts(MOUNTS);
+        let summary: Vec<(&str, &Path, &str, bool)> = mounts.iter()
+            .map(|mount| (mount.device.as_str(), mount.mount_point.as_path(), mount.fs_type.as_str(), mount.read_only))
+            .collect();
+        assert_eq!(summary, [
+            ("/dev/sda2", Path::new("/"), "ext4", false),
+            ("proc", Path::new("/proc"), "proc", false),
+            ("/dev/sda3", Path::new("/home"), "ext4", false),
+            ("/dev/sdb1", Path::new("/media/me/USB Stick"), "vfat", true),
+        ]);
+        assert!(mounts[1].is_pseudo());
+        assert!(!mounts[0].is_pseudo());
+    }
+
+    #[test]
+    fn unescape_decodes_octal_sequences() {
+        assert_eq!(unescape("My\\040Disk"), "My Disk");
+        assert_eq!(unescape("tab\\011and\\134backslash"), "tab\tand\\backslash");
+        // Séquence incomplète ou invalide : gardée telle quelle
+        assert_eq!(unescape("end\\04"), "end\\04");
+        assert_eq!(unescape("bad\\089"), "bad\\089");
+    }
+
+    #[test]
+    fn longest_mount_point_prefers_the_deepest_mount() {
+        let mount_points = [Path::new("/"), Path::new("/home"), Path::new("/home/me/data")];
+        assert_eq!(longest_mount_point(Path::new("/home/me/docs"), mount_points), Some(Path::new("/home")));
+        assert_eq!(longest_mount_point(Path::new("/home/me/data/x"), mount_points), Some(Path::new("/home/me/data")));
+        // Comparaison par composants : /homework n'est pas sous /home
+        assert_eq!(longest_mount_point(Path::new("/homework"), mount_points), Some(Path::new("/")));
+        assert_eq!(longest_mount_point(Path::new("relative/path"), mount_points), None);
+    }
+
+    #[test]
+    fn volume_for_matches_the_longest_mount_point() {
+        let volumes = [volume("/"), volume("/diskvader-test-mount")];
+        let found = volume_for(Path::new("/diskvader-test-mount/projects"), &volumes).unwrap();
+        assert_eq!(found.mount_point, PathBuf::from("/diskvader-test-mount"));
+        assert!(volume_for(Path::new("/x"), &volumes[1..]).is_none());
+    }
+
+    #[test]
+    fn used_percentage_ignores_the_reserve() {
+        let usage = FilesystemUsage { total: 1000, used: 450, free: 550, available: 500, reserved: 50, ..FilesystemUsage::default() };
+        assert!((usage.used_percentage() - 47.368_42).abs() < 0.001);
+        assert_eq!(FilesystemUsage::default().used_percentage(), 0.0);
+    }
+}
@@ -1,188 +1,71 @@
-use tauri::State;
-use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
+use chrono::Local;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::models::{CommandError, CleanupSuggestionItem, ScannedFile, SharedScanResults};
+use diskvader_core::analyzers;
+use diskvader_core::artifacts::{self, DevProject};
+use diskvader_core::archive::{self, ArchiveFormat, CompressionReport};
+use diskvader_core::caches::{self, CacheOwner};
+use diskvader_core::cleanup::{self, CleanupBatch, CleanupOptions, CleanupPreview, CleanupTarget, SharedCleanupJournal, SharedCleanupPreview, UndoReport};
+use diskvader_core::dedup::{self, DuplicateGroup, LinkMode};
+use diskvader_core::models::{CommandError, CleanupSuggestionItem, SharedScanResults, TrashInfo};
+use diskvader_core::offload::{self, ConflictPolicy, OffloadOptions, OffloadRecord, OffloadReport, OffloadTarget, RestoreReport, SharedOffloadManifest};
+use diskvader_core::operation::{OperationCancellation, OperationProgress, OperationSink};
+use diskvader_core::protected::{ProtectedPathsInfo, SharedProtectedPaths};
+use diskvader_core::report::{Report, ReportOptions};
+use diskvader_core::thumbnails::{self, OrphanedThumbnail};
+use diskvader_core::trashbin::{self, TrashItem, TrashReport, TrashVolume};
 
-#[tauri::command]
-pub fn get_cleanup_suggestions(scan_results: State<'_, SharedScanResults>) -> Result<Vec<CleanupSuggestionItem>, CommandError> {
-    let results = scan_results.lock().unwrap();
-    let mut suggestions = Vec::new();
-    
-    let mut size_groups: HashMap<u64, Vec<&ScannedFile>> = HashMap::new();
-    for file in &results.largest_files {
-        size_groups.entry(file.size).or_insert_with(Vec::new).push(file);
-    }
-    
-    let mut duplicate_size = 0u64;
-    let mut duplicate_count = 0u32;
-    for (size, files) in size_groups {
-        if files.len() > 1 && size > 10_000 { 
-            duplicate_size += size * (files.len() as u64 - 1); 
-            duplicate_count += files.len() as u32 - 1;
-        }
-    }
-    
-    if duplicate_count > 0 {
-        suggestions.push(CleanupSuggestionItem {
-            cleanup_type: "Potential Duplicate Files".to_string(),
-            size: duplicate_size,
-            count: duplicate_count,
-            color_class: "blue".to_string(),
-        });
-    }
-    
-    let mut backup_size = 0u64;
-    let mut backup_count = 0u32;
-    for file in &results.largest_files {
-        let name_lower = file.name.to_lowercase();
-        if name_lower.contains("backup") || name_lower.contains("bak") || 
-           name_lower.contains("~") || name_lower.ends_with(".old") ||
-           name_lower.contains("copy") || name_lower.contains("temp") {
-            backup_size += file.size;
-            backup_count += 1;
-        }
-    }
-    
-    if backup_count > 0 {
-        suggestions.push(CleanupSuggestionItem {
-            cleanup_type: "Backup Files".to_string(),
-            size: backup_size,
-            count: backup_count,
-            color_class: "green".to_string(),
-        });
-    }
-    
-    let mut empty_folders = 0u32;
-    for folder in &results.all_folders {
-        if folder.file_count == 0 {
-            empty_folders += 1;
-        }
-    }
-    
-    if empty_folders > 0 {
-        suggestions.push(CleanupSuggestionItem {
-            cleanup_type: "Empty Folders".to_string(),
-            size: 0,
-            count: empty_folders,
-            color_class: "yellow".to_string(),
-        });
-    }
-    
-    let mut old_large_files_size = 0u64;
-    let mut old_large_files_count = 0u32;
-    let now = SystemTime::now();
-    
-    for file in &results.largest_files {
-        if file.size > 100_000_000 { 
-            if let Ok(metadata) = fs::metadata(&file.path) {
-                if let Ok(modified) = metadata.modified() {
-                    if let Ok(duration) = now.duration_since(modified) {
-                        if duration.as_secs() > 365 * 24 * 3600 { 
-                            old_large_files_size += file.size;
-                            old_large_files_count += 1;
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    if old_large_files_count > 0 {
-        suggestions.push(CleanupSuggestionItem {
-            cleanup_type: "Old Large Files (>1 year)".to_string(),
-            size: old_large_files_size,
-            count: old_large_files_count,
-            color_class: "red".to_string(),
-        });
-    }
-    
-    let mut temp_size = 0u64;
-    let mut temp_count = 0u32;
-    for file in &results.largest_files {
-        let name_lower = file.name.to_lowercase();
-        let path_lower = file.path.to_string_lossy().to_lowercase();
-        if name_lower.starts_with("tmp") || name_lower.starts_with("temp") ||
-           path_lower.contains("/tmp/") || path_lower.contains("/temp/") ||
-           path_lower.contains("\\tmp\\") || path_lower.contains("\\temp\\") ||
-           file.extension == "tmp" || file.extension == "temp" {
-            temp_size += file.size;
-            temp_count += 1;
-        }
+use crate::snapshot_handler::{pick_path, DialogMode};
+
+pub struct TauriOperationSink {
+    app: AppHandle,
+}
+
+impl TauriOperationSink {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
     }
-    
-    if temp_count > 0 {
-        suggestions.push(CleanupSuggestionItem {
-            cleanup_type: "Temporary Files".to_string(),
-            size: temp_size,
-            count: temp_count,
-            color_class: "orange".to_string(),
-        });
+}
+
+impl OperationSink for TauriOperationSink {
+    fn emit_operation_progress(&self, progress: OperationProgress) {
+        let _ = self.app.emit("operation_progress", progress);
     }
-    
-    suggestions.sort_by(|a, b| b.size.cmp(&a.size));
-    
-    Ok(suggestions)
 }
 
 #[tauri::command]
-pub fn export_report(scan_results: State<'_, SharedScanResults>) -> Result<(), CommandError> {
+pub fn get_cleanup_suggestions(scan_results: State<'_, SharedScanResults>) -> Result<Vec<CleanupSuggestionItem>, CommandError> {
     let results = scan_results.lock().unwrap();
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    let report_filename = format!("disk_analysis_report_{}.txt", timestamp);
-    
-    let mut report_content = String::new();
-    report_content.push_str("=== DISK ANALYSIS REPORT ===\n\n");
-    
-    report_content.push_str(&format!("Scan Path: {}\n", results.scan_path));
-    report_content.push_str(&format!("Scan Date: {}\n", 
-        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()));
-    report_content.push_str(&format!("Scan Duration: {:.2} seconds\n", results.scan_time));
-    report_content.push_str(&format!("Total Files: {}\n", results.total_files));
-    report_content.push_str(&format!("Total Folders: {}\n", results.total_folders));
-    report_content.push_str(&format!("Total Size: {:.2} GB\n\n", results.total_size as f64 / 1_000_000_000.0));
-    
-    report_content.push_str("=== TOP 10 LARGEST FILES ===\n");
-    for (index, file) in results.largest_files.iter().take(10).enumerate() {
-        report_content.push_str(&format!("{}. {} - {:.2} MB ({})\n", 
-            index + 1, 
-            file.name, 
-            file.size as f64 / 1_000_000.0,
-            file.path.to_string_lossy()));
-    }
-    report_content.push_str("\n");
-    
-    report_content.push_str("=== TOP 10 LARGEST FOLDERS ===\n");
-    for (index, folder) in results.folders.iter().take(10).enumerate() {
-        report_content.push_str(&format!("{}. {} - {:.2} GB ({} files)\n", 
-            index + 1, 
-            folder.name, 
-            folder.size as f64 / 1_000_000_000.0,
-            folder.file_count));
-    }
-    report_content.push_str("\n");
-    
-    report_content.push_str("=== FILE TYPE DISTRIBUTION ===\n");
-    let mut file_types: Vec<_> = results.file_type_distribution.iter().collect();
-    file_types.sort_by(|a, b| b.1.0.cmp(&a.1.0)); 
-    
-    for (file_type, (size, count)) in file_types {
-        report_content.push_str(&format!("{}: {:.2} GB ({} files)\n", 
-            file_type, 
-            *size as f64 / 1_000_000_000.0, 
-            count));
-    }
-    
-    match fs::write(&report_filename, report_content) {
+    Ok(analyzers::cleanup_suggestions(&results))
+}
+
+#[tauri::command]
+pub async fn export_report(app: AppHandle, options: Option<ReportOptions>, path: Option<String>, scan_results: State<'_, SharedScanResults>) -> Result<Option<String>, CommandError> {
+    let options = options.unwrap_or_default();
+    let extension = options.format.extension();
+
+    let path = match path {
+        Some(path) => Some(path),
+        None => {
+            let default_name = format!("disk_analysis_report_{}.{}", Local::now().format("%Y%m%d_%H%M%S"), extension);
+            pick_path(&app, "Disk analysis report", &[extension], DialogMode::Save(&default_name))?
+        }
+    };
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let report_content = {
+        let results = scan_results.lock().unwrap();
+        Report::from_results(&results, options.top_files, options.top_folders).render(options.format)
+    };
+
+    match fs::write(&path, report_content) {
         Ok(()) => {
-            println!("Report exported to: {}", report_filename);
-            Ok(())
+            println!("Report exported to: {}", path);
+            Ok(Some(path))
         }
         Err(e) => {
             println!("Failed to export report: {}", e);
@@ -192,7 +75,16 @@ pub fn export_report(scan_results: State<'_, SharedScanResults>) -> Result<(), C
 }
 
 #[tauri::command]
-pub fn get_trash_info() -> Result<crate::models::TrashInfo, CommandError> {
+pub fn get_trash_info() -> Result<TrashInfo, CommandError> {
+    // Corbeilles FreeDesktop : un élément par .trashinfo, tailles des dossiers tirées du cache directorysizes
+    if !trashbin::trash_directories().is_empty() {
+        let items = trashbin::list_items();
+        return Ok(TrashInfo {
+            size: items.iter().map(|item| item.size).sum(),
+            count: items.len() as u32,
+        });
+    }
+
     let mut total_size = 0u64;
     let mut total_count = 0u32;
     
@@ -201,10 +93,6 @@ pub fn get_trash_info() -> Result<crate::models::TrashInfo, CommandError> {
         vec![
             PathBuf::from(format!("{}/.Trash", std::env::var("HOME").unwrap_or_default())),
         ]
-    } else if cfg!(target_os = "linux") {
-        vec![
-            PathBuf::from(format!("{}/.local/share/Trash/files", std::env::var("HOME").unwrap_or_default())),
-        ]
     } else if cfg!(target_os = "windows") {
         // Sur Windows, c'est plus complexe car la corbeille est gérée par le système
         // Pour l'instant, on retourne des données vides
@@ -234,7 +122,7 @@ pub fn get_trash_info() -> Result<crate::models::TrashInfo, CommandError> {
         }
     }
     
-    Ok(crate::models::TrashInfo {
+    Ok(TrashInfo {
         size: total_size,
         count: total_count,
     })
@@ -266,16 +154,20 @@ fn calculate_directory_size(path: &Path) -> Result<(u64, u32), std::io::Error> {
 }
 
 #[tauri::command]
-pub fn empty_trash() -> Result<(), CommandError> {
+pub fn empty_trash(trash_dir: Option<PathBuf>, all_volumes: Option<bool>) -> Result<(), CommandError> {
+    if !trashbin::trash_directories().is_empty() {
+        let report = trashbin::empty(trash_dir.as_deref(), all_volumes.unwrap_or(false))?;
+        for failure in &report.failures {
+            println!("Warning: Failed to remove {:?}, {}", failure.path, failure.error);
+        }
+        println!("Trash emptied successfully ({} bytes freed)", report.freed_size);
+        return Ok(());
+    }
+
     let trash_paths = if cfg!(target_os = "macos") {
         vec![
             PathBuf::from(format!("{}/.Trash", std::env::var("HOME").unwrap_or_default())),
         ]
-    } else if cfg!(target_os = "linux") {
-        vec![
-            PathBuf::from(format!("{}/.local/share/Trash/files", std::env::var("HOME").unwrap_or_default())),
-            PathBuf::from(format!("{}/.local/share/Trash/info", std::env::var("HOME").unwrap_or_default())),
-        ]
     } else if cfg!(target_os = "windows") {
         // Sur Windows, c'est plus complexe car la corbeille est gérée par le système
         // Pour l'instant, on retourne des données vides
@@ -308,184 +200,319 @@ pub fn empty_trash() -> Result<(), CommandError> {
 }
 
 #[tauri::command]
-pub fn delete_file(file_id: u32, scan_results: State<'_, SharedScanResults>) -> Result<(), CommandError> {
-    let results = scan_results.lock().unwrap();
-    
-    if let Some(file) = results.largest_files.get((file_id - 1) as usize) {
-        let file_path = &file.path;
-        
-        if !file_path.exists() {
-            return Err(CommandError::PathDoesNotExist(file_path.to_string_lossy().to_string()));
+pub fn get_trash_volumes() -> Result<Vec<TrashVolume>, CommandError> {
+    Ok(trashbin::volumes_summary())
+}
+
+#[tauri::command]
+pub fn list_trash_items() -> Result<Vec<TrashItem>, CommandError> {
+    Ok(trashbin::list_items())
+}
+
+#[tauri::command]
+pub fn restore_trash_items(item_ids: Vec<String>, protected: State<'_, SharedProtectedPaths>) -> Result<TrashReport, CommandError> {
+    let report = trashbin::restore_items(&item_ids, &protected.lock().unwrap());
+    println!("Restored {} items from the trash ({} failures)", report.paths.len(), report.failures.len());
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn purge_trash_items(item_ids: Option<Vec<String>>, older_than_days: Option<u32>, trash_dir: Option<PathBuf>, all_volumes: Option<bool>) -> Result<TrashReport, CommandError> {
+    let report = match (item_ids, older_than_days) {
+        (Some(item_ids), _) => trashbin::purge_items(&item_ids),
+        (None, Some(days)) => trashbin::purge_older_than(days, trash_dir.as_deref(), all_volumes.unwrap_or(false))?,
+        (None, None) => return Err(CommandError::InternalError("Select items or an age to purge".to_string())),
+    };
+    println!("Purged {} items from the trash, {} bytes freed", report.paths.len(), report.freed_size);
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn delete_file(file_id: String, confirm_changed: Option<bool>, scan_results: State<'_, SharedScanResults>, protected: State<'_, SharedProtectedPaths>, journal: State<'_, SharedCleanupJournal>) -> Result<CleanupBatch, CommandError> {
+    let target = {
+        let results = scan_results.lock().unwrap();
+        let file = results.find_file(&file_id)?;
+        CleanupTarget {
+            path: file.path.clone(),
+            size: file.size,
+            is_dir: false,
+            identity: file.identity.clone(),
         }
-        
-        match trash::delete(file_path) {
-            Ok(()) => {
-                println!("Successfully moved file to trash: {:?}", file_path);
-                Ok(())
-            }
-            Err(e) => {
-                println!("Failed to move file to trash {:?}: {}", file_path, e);
-                Err(CommandError::InternalError(format!("Failed to move file to trash: {}", e)))
-            }
+    };
+
+    if fs::symlink_metadata(&target.path).is_err() {
+        return Err(CommandError::PathDoesNotExist(target.path.to_string_lossy().to_string()));
+    }
+
+    let options = CleanupOptions {
+        permanent: false,
+        confirm_changed: confirm_changed.unwrap_or(false),
+    };
+    let batch = cleanup::execute_cleanup(&[target], vec!["Delete File".to_string()], options, &protected.lock().unwrap());
+    if let Some(failure) = batch.failures.first() {
+        println!("Failed to move file to trash {:?}: {}", failure.path, failure.error);
+        if failure.needs_confirmation {
+            return Err(CommandError::ConfirmationRequired(failure.error.clone()));
         }
-    } else {
-        Err(CommandError::InternalError(format!("File with ID {} not found", file_id)))
+        return Err(CommandError::InternalError(format!("Failed to move file to trash: {}", failure.error)));
     }
+
+    println!("Successfully moved file to trash: {:?}", batch.entries[0].original_path);
+    journal.lock().unwrap().record(batch.clone())?;
+    Ok(batch)
 }
 
 #[tauri::command]
-pub fn compress_files(file_ids: Vec<u32>, scan_results: State<'_, SharedScanResults>) -> Result<(), CommandError> {
-    let results = scan_results.lock().unwrap();
-    
-    let mut files_to_compress = Vec::new();
-    for file_id in file_ids {
-        if let Some(file) = results.largest_files.get((file_id - 1) as usize) {
-            if file.path.exists() {
-                files_to_compress.push(&file.path);
+pub async fn compress_files(app: AppHandle, file_ids: Vec<String>, format: Option<ArchiveFormat>, destination: Option<String>, trash_originals: Option<bool>, confirm_changed: Option<bool>, scan_results: State<'_, SharedScanResults>, protected: State<'_, SharedProtectedPaths>, cancellation: State<'_, OperationCancellation>, journal: State<'_, SharedCleanupJournal>, manifest: State<'_, SharedOffloadManifest>) -> Result<Option<CompressionReport>, CommandError> {
+    let format = format.unwrap_or_default();
+    let options = CleanupOptions {
+        permanent: false,
+        confirm_changed: confirm_changed.unwrap_or(false),
+    };
+
+    let targets = {
+        let results = scan_results.lock().unwrap();
+        let protected = protected.lock().unwrap();
+        let mut targets = Vec::new();
+        let mut violations = Vec::new();
+        for file_id in file_ids {
+            let file = results.find_file(&file_id)?;
+            if let Some(reason) = protected.violation(&file.path) {
+                violations.push(format!("{}: {}", file.path.display(), reason));
+            } else {
+                targets.push(CleanupTarget {
+                    path: file.path.clone(),
+                    size: file.size,
+                    is_dir: false,
+                    identity: file.identity.clone(),
+                });
             }
         }
-    }
-    
-    if files_to_compress.is_empty() {
+        if !violations.is_empty() {
+            return Err(CommandError::ProtectedPath(violations.join("; ")));
+        }
+        targets
+    };
+
+    if targets.is_empty() {
         return Err(CommandError::InternalError("No valid files found to compress".to_string()));
     }
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    let archive_name = format!("compressed_files_{}.zip", timestamp);
-    
-    println!("Would compress {} files into {}", files_to_compress.len(), archive_name);
-    for file_path in files_to_compress {
-        println!("  - {:?}", file_path);
+    for target in &targets {
+        if let Err(failure) = cleanup::verify_target(target, options.confirm_changed) {
+            return Err(if failure.needs_confirmation {
+                CommandError::ConfirmationRequired(format!("{}: {}", failure.path.display(), failure.error))
+            } else {
+                CommandError::InternalError(format!("{}: {}", failure.path.display(), failure.error))
+            });
+        }
     }
-    
-    println!("Compression completed (simulated)");
-    Ok(())
+
+    let destination = match destination {
+        Some(destination) => Some(destination),
+        None => {
+            let default_name = format!("compressed_files_{}.{}", Local::now().format("%Y%m%d_%H%M%S"), format.extension());
+            pick_path(&app, "Archive", &[format.extension()], DialogMode::Save(&default_name))?
+        }
+    };
+    let Some(destination) = destination else {
+        return Ok(None);
+    };
+
+    cancellation.reset();
+    let sources: Vec<PathBuf> = targets.iter().map(|target| target.path.clone()).collect();
+    let summary = archive::create_archive(&sources, Path::new(&destination), format, &TauriOperationSink::new(app), cancellation.flag())?;
+    println!("Compressed {} files into {}", summary.entries.len(), destination);
+
+    let trashed = if trash_originals.unwrap_or(false) {
+        let batch = cleanup::execute_cleanup(&targets, vec!["Compressed Files".to_string()], options, &protected.lock().unwrap());
+        journal.lock().unwrap().record(batch.clone())?;
+        Some(batch)
+    } else {
+        None
+    };
+
+    // Les entrées de l'archive rejoignent le manifeste pour pouvoir être restaurées
+    let removed: Vec<PathBuf> = trashed.iter()
+        .flat_map(|batch| batch.entries.iter().map(|entry| entry.original_path.clone()))
+        .collect();
+    manifest.lock().unwrap().record(&offload::archive_records(&summary, &removed))?;
+
+    Ok(Some(CompressionReport::new(summary, trashed)))
 }
 
 #[tauri::command]
-pub fn move_to_cloud(file_ids: Vec<u32>, scan_results: State<'_, SharedScanResults>) -> Result<(), CommandError> {
+pub fn get_cache_owners(scan_results: State<'_, SharedScanResults>) -> Result<Vec<CacheOwner>, CommandError> {
     let results = scan_results.lock().unwrap();
-    
-    let mut files_to_move = Vec::new();
-    let mut total_size = 0u64;
-    
-    for file_id in file_ids {
-        if let Some(file) = results.largest_files.get((file_id - 1) as usize) {
-            if file.path.exists() {
-                files_to_move.push((file.path.clone(), file.size));
-                total_size += file.size;
-            }
-        }
+    Ok(caches::cache_owners(&results))
+}
+
+#[tauri::command]
+pub async fn get_orphaned_thumbnails() -> Result<Vec<OrphanedThumbnail>, CommandError> {
+    let orphans = thumbnails::orphaned_thumbnails();
+    println!("Found {} orphaned thumbnails", orphans.len());
+    Ok(orphans)
+}
+
+#[tauri::command]
+pub fn get_developer_artifacts(scan_results: State<'_, SharedScanResults>) -> Result<Vec<DevProject>, CommandError> {
+    let results = scan_results.lock().unwrap();
+    Ok(artifacts::developer_projects(&results))
+}
+
+#[tauri::command]
+pub async fn find_duplicates(scan_results: State<'_, SharedScanResults>) -> Result<Vec<DuplicateGroup>, CommandError> {
+    let results = scan_results.lock().unwrap().clone();
+    let groups = dedup::verified_duplicate_groups(&results);
+    println!("Found {} groups of identical files", groups.len());
+    Ok(groups)
+}
+
+#[tauri::command]
+pub fn link_duplicates(groups: Vec<Vec<String>>, mode: Option<LinkMode>, confirm_changed: Option<bool>, scan_results: State<'_, SharedScanResults>, protected: State<'_, SharedProtectedPaths>, journal: State<'_, SharedCleanupJournal>) -> Result<CleanupBatch, CommandError> {
+    let targets = {
+        let results = scan_results.lock().unwrap();
+        groups.iter()
+            .map(|file_ids| dedup::link_targets(&results, file_ids))
+            .collect::<Result<Vec<_>, CommandError>>()?
+    };
+
+    let batch = dedup::link_duplicates(&targets, mode.unwrap_or_default(), confirm_changed.unwrap_or(false), &protected.lock().unwrap());
+    println!("Linked {} duplicates, {} bytes freed ({} failures)", batch.entries.len(), batch.freed_size, batch.failures.len());
+    if !batch.entries.is_empty() {
+        journal.lock().unwrap().record(batch.clone())?;
     }
-    
-    if files_to_move.is_empty() {
+    Ok(batch)
+}
+
+#[tauri::command]
+pub fn cancel_operation(cancellation: State<'_, OperationCancellation>) -> Result<(), CommandError> {
+    println!("Cancelling file operation...");
+    cancellation.cancel();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn move_to_cloud(app: AppHandle, file_ids: Vec<String>, target: Option<OffloadTarget>, remove_local: Option<bool>, confirm_changed: Option<bool>, scan_results: State<'_, SharedScanResults>, protected: State<'_, SharedProtectedPaths>, cancellation: State<'_, OperationCancellation>, manifest: State<'_, SharedOffloadManifest>) -> Result<Option<OffloadReport>, CommandError> {
+    let targets = {
+        let results = scan_results.lock().unwrap();
+        file_ids.iter()
+            .map(|file_id| {
+                let file = results.find_file(file_id)?;
+                Ok(CleanupTarget {
+                    path: file.path.clone(),
+                    size: file.size,
+                    is_dir: false,
+                    identity: file.identity.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, CommandError>>()?
+    };
+    if targets.is_empty() {
         return Err(CommandError::InternalError("No valid files found to move".to_string()));
     }
-    
-    println!("Would move {} files ({:.2} GB) to cloud:", files_to_move.len(), total_size as f64 / 1_000_000_000.0);
-    for (file_path, size) in files_to_move {
-        println!("  - {:?} ({:.2} MB)", file_path, size as f64 / 1_000_000.0);
-    }
-    
-    println!("Cloud move completed (simulated)");
-    Ok(())
+
+    let target = match target {
+        Some(target) => Some(target),
+        None => pick_path(&app, "Offload folder", &[], DialogMode::Folder)?
+            .map(|path| OffloadTarget::Local { path: PathBuf::from(path) }),
+    };
+    let Some(target) = target else {
+        return Ok(None);
+    };
+
+    let options = OffloadOptions {
+        remove_local: remove_local.unwrap_or(false),
+        confirm_changed: confirm_changed.unwrap_or(false),
+    };
+    cancellation.reset();
+    let report = offload::offload_files(&targets, &target, options, &protected.lock().unwrap(), &TauriOperationSink::new(app), cancellation.flag())?;
+    manifest.lock().unwrap().record(&report.records)?;
+
+    println!("Offloaded {} files to {} ({} failures)", report.records.len(), report.destination, report.failures.len());
+    Ok(Some(report))
 }
 
 #[tauri::command]
-pub fn clean_selected_items(items: Vec<CleanupSuggestionItem>, scan_results: State<'_, SharedScanResults>) -> Result<(), CommandError> {
-    let results = scan_results.lock().unwrap();
-    
-    for item in items {
-        match item.cleanup_type.as_str() {
-            "Potential Duplicate Files" => {
-                let mut size_groups: HashMap<u64, Vec<&ScannedFile>> = HashMap::new();
-                for file in &results.largest_files {
-                    size_groups.entry(file.size).or_insert_with(Vec::new).push(file);
-                }
-                
-                for (size, files) in size_groups {
-                    if files.len() > 1 && size > 10_000 {
-                        for file in files.iter().skip(1) {
-                            if file.path.exists() {
-                                match fs::remove_file(&file.path) {
-                                    Ok(()) => println!("Removed duplicate file: {:?}", file.path),
-                                    Err(e) => println!("Failed to remove duplicate {:?}: {}", file.path, e),
-                                }
-                            }
-                        }
-                    }
-                }
-            },
-            "Backup Files" => {
-                for file in &results.largest_files {
-                    let name_lower = file.name.to_lowercase();
-                    if name_lower.contains("backup") || name_lower.contains("bak") || 
-                       name_lower.contains("~") || name_lower.ends_with(".old") ||
-                       name_lower.contains("copy") || name_lower.contains("temp") {
-                        if file.path.exists() {
-                            match fs::remove_file(&file.path) {
-                                Ok(()) => println!("Removed backup file: {:?}", file.path),
-                                Err(e) => println!("Failed to remove backup {:?}: {}", file.path, e),
-                            }
-                        }
-                    }
-                }
-            },
-            "Empty Folders" => {
-                for folder in &results.all_folders {
-                    if folder.file_count == 0 && folder.path.exists() {
-                        match fs::remove_dir(&folder.path) {
-                            Ok(()) => println!("Removed empty folder: {:?}", folder.path),
-                            Err(e) => println!("Failed to remove empty folder {:?}: {}", folder.path, e),
-                        }
-                    }
-                }
-            },
-            "Old Large Files (>1 year)" => {
-                let now = SystemTime::now();
-                for file in &results.largest_files {
-                    if file.size > 100_000_000 && file.path.exists() {
-                        if let Ok(metadata) = fs::metadata(&file.path) {
-                            if let Ok(modified) = metadata.modified() {
-                                if let Ok(duration) = now.duration_since(modified) {
-                                    if duration.as_secs() > 365 * 24 * 3600 {
-                                        match fs::remove_file(&file.path) {
-                                            Ok(()) => println!("Removed old large file: {:?}", file.path),
-                                            Err(e) => println!("Failed to remove old file {:?}: {}", file.path, e),
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            },
-            "Temporary Files" => {
-                for file in &results.largest_files {
-                    let name_lower = file.name.to_lowercase();
-                    let path_lower = file.path.to_string_lossy().to_lowercase();
-                    if name_lower.starts_with("tmp") || name_lower.starts_with("temp") ||
-                       path_lower.contains("/tmp/") || path_lower.contains("/temp/") ||
-                       path_lower.contains("\\tmp\\") || path_lower.contains("\\temp\\") ||
-                       file.extension == "tmp" || file.extension == "temp" {
-                        if file.path.exists() {
-                            match fs::remove_file(&file.path) {
-                                Ok(()) => println!("Removed temporary file: {:?}", file.path),
-                                Err(e) => println!("Failed to remove temp file {:?}: {}", file.path, e),
-                            }
-                        }
-                    }
-                }
-            },
-            _ => {
-                println!("Unknown cleanup type: {}", item.cleanup_type);
-            }
+pub fn get_offload_manifest(manifest: State<'_, SharedOffloadManifest>) -> Result<Vec<OffloadRecord>, CommandError> {
+    Ok(manifest.lock().unwrap().records().to_vec())
+}
+
+#[tauri::command]
+pub async fn restore_files(record_ids: Vec<String>, conflict: Option<ConflictPolicy>, secret_key: Option<String>, protected: State<'_, SharedProtectedPaths>, manifest: State<'_, SharedOffloadManifest>) -> Result<RestoreReport, CommandError> {
+    let mut manifest = manifest.lock().unwrap();
+    let report = offload::restore_records(manifest.records_mut(), &record_ids, conflict.unwrap_or_default(), secret_key.as_deref(), &protected.lock().unwrap());
+    manifest.save()?;
+
+    println!("Restored {} files ({} failures)", report.restored.len(), report.failures.len());
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn preview_cleanup(scan_results: State<'_, SharedScanResults>, preview: State<'_, SharedCleanupPreview>) -> Result<CleanupPreview, CommandError> {
+    let new_preview = {
+        let results = scan_results.lock().unwrap();
+        CleanupPreview::from_results(&results)
+    };
+    *preview.lock().unwrap() = Some(new_preview.clone());
+    Ok(new_preview)
+}
+
+#[tauri::command]
+pub fn clean_selected_items(preview_id: String, items: Vec<CleanupSuggestionItem>, paths: Option<Vec<PathBuf>>, permanent: Option<bool>, confirm_changed: Option<bool>, scan_results: State<'_, SharedScanResults>, preview: State<'_, SharedCleanupPreview>, protected: State<'_, SharedProtectedPaths>, journal: State<'_, SharedCleanupJournal>) -> Result<CleanupBatch, CommandError> {
+    let options = CleanupOptions {
+        permanent: permanent.unwrap_or(false),
+        confirm_changed: confirm_changed.unwrap_or(false),
+    };
+    let cleanup_types: Vec<String> = items.into_iter().map(|item| item.cleanup_type).collect();
+
+    let (targets, rejected) = {
+        let preview = preview.lock().unwrap();
+        let preview = preview.as_ref()
+            .filter(|preview| preview.preview_id == preview_id)
+            .ok_or_else(|| CommandError::InternalError(format!("Cleanup preview {} not found, preview the cleanup again", preview_id)))?;
+        if !preview.matches(&scan_results.lock().unwrap()) {
+            return Err(CommandError::InternalError("Cleanup preview is out of date, preview the cleanup again".to_string()));
         }
-    }
-    
-    println!("Cleanup completed");
-    Ok(())
-}
\ No newline at end of file
+        preview.targets(&cleanup_types, paths.as_deref())
+    };
+
+    let mut batch = cleanup::execute_cleanup(&targets, cleanup_types, options, &protected.lock().unwrap());
+    batch.failures.extend(rejected);
+    journal.lock().unwrap().record(batch.clone())?;
+
+    println!("Cleanup completed: batch {} ({} items, {} failures)", batch.batch_id, batch.entries.len(), batch.failures.len());
+    Ok(batch)
+}
+
+#[tauri::command]
+pub fn undo_cleanup(batch_id: String, journal: State<'_, SharedCleanupJournal>) -> Result<UndoReport, CommandError> {
+    let mut journal = journal.lock().unwrap();
+    let batch = journal
+        .batch_mut(&batch_id)
+        .ok_or_else(|| CommandError::InternalError(format!("Cleanup batch {} not found", batch_id)))?;
+
+    let report = cleanup::undo_batch(batch)?;
+    journal.save()?;
+
+    println!("Undo of batch {}: {} restored, {} failures", batch_id, report.restored.len(), report.failures.len());
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn get_protected_paths(protected: State<'_, SharedProtectedPaths>) -> Result<ProtectedPathsInfo, CommandError> {
+    Ok(protected.lock().unwrap().info())
+}
+
+#[tauri::command]
+pub fn set_protected_paths(paths: Vec<PathBuf>, protected: State<'_, SharedProtectedPaths>) -> Result<ProtectedPathsInfo, CommandError> {
+    let mut protected = protected.lock().unwrap();
+    protected.set_user_paths(paths)?;
+    println!("Protected paths updated");
+    Ok(protected.info())
+}
+
+#[tauri::command]
+pub fn get_cleanup_history(journal: State<'_, SharedCleanupJournal>) -> Result<Vec<CleanupBatch>, CommandError> {
+    let journal = journal.lock().unwrap();
+    Ok(journal.batches().iter().rev().cloned().collect())
+}
@@ -1,27 +1,68 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod models;
 mod scan_handler;
 mod file_operations_handler;
+mod snapshot_handler;
 
 use std::sync::{Arc, Mutex};
+use tauri::Manager;
 
-use models::SharedScanResults;
+use diskvader_core::cleanup::{CleanupJournal, SharedCleanupJournal, SharedCleanupPreview};
+use diskvader_core::models::{self, SharedScanResults};
+use diskvader_core::offload::{OffloadManifest, SharedOffloadManifest};
+use diskvader_core::operation::OperationCancellation;
+use diskvader_core::protected::{ProtectedPaths, SharedProtectedPaths};
 
 fn main() {
     let scan_results: SharedScanResults = Arc::new(Mutex::new(models::ScanResults::default()));
+    let cleanup_preview: SharedCleanupPreview = Arc::new(Mutex::new(None));
     
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .manage(scan_results)
+        .manage(cleanup_preview)
+        .manage(OperationCancellation::default())
+        .setup(|app| {
+            // Le journal de nettoyage, le manifeste de déchargement et la liste des chemins
+            // protégés survivent aux redémarrages
+            let (journal, manifest, protected) = match app.path().app_data_dir() {
+                Ok(dir) => {
+                    let mut protected = ProtectedPaths::load(dir.join("protected-paths.json"));
+                    protected.protect(dir.clone());
+                    (
+                        CleanupJournal::load(dir.join("cleanup-journal.json")),
+                        OffloadManifest::load(dir.join("offload-manifest.json")),
+                        protected,
+                    )
+                }
+                Err(e) => {
+                    println!("Warning: no app data directory, cleanup journal kept in memory: {}", e);
+                    (CleanupJournal::in_memory(), OffloadManifest::in_memory(), ProtectedPaths::default())
+                }
+            };
+            let journal: SharedCleanupJournal = Arc::new(Mutex::new(journal));
+            let manifest: SharedOffloadManifest = Arc::new(Mutex::new(manifest));
+            let protected: SharedProtectedPaths = Arc::new(Mutex::new(protected));
+            app.manage(journal);
+            app.manage(manifest);
+            app.manage(protected);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             scan_handler::select_folder,
             scan_handler::start_scan,
             scan_handler::cancel_scan,
             scan_handler::get_scan_results,
+            scan_handler::get_deleted_open_files,
+            scan_handler::get_disk_accounting,
+            scan_handler::list_volumes,
             scan_handler::get_largest_files,
             scan_handler::get_folders,
+            scan_handler::get_folders_by_entries,
+            scan_handler::get_inode_usage,
+            scan_handler::get_age_histograms,
+            scan_handler::get_size_histogram,
             scan_handler::get_all_folders,
             scan_handler::get_folder_files,
             scan_handler::get_file_type_distribution,
@@ -37,11 +78,33 @@ fn main() {
             file_operations_handler::get_cleanup_suggestions,
             file_operations_handler::get_trash_info,
             file_operations_handler::empty_trash,
+            file_operations_handler::get_trash_volumes,
+            file_operations_handler::list_trash_items,
+            file_operations_handler::restore_trash_items,
+            file_operations_handler::purge_trash_items,
             file_operations_handler::delete_file,
             file_operations_handler::compress_files,
+            file_operations_handler::get_cache_owners,
+            file_operations_handler::get_orphaned_thumbnails,
+            file_operations_handler::get_developer_artifacts,
+            file_operations_handler::find_duplicates,
+            file_operations_handler::link_duplicates,
+            file_operations_handler::cancel_operation,
             file_operations_handler::move_to_cloud,
+            file_operations_handler::get_offload_manifest,
+            file_operations_handler::restore_files,
+            file_operations_handler::preview_cleanup,
             file_operations_handler::clean_selected_items,
+            file_operations_handler::undo_cleanup,
+            file_operations_handler::get_cleanup_history,
+            file_operations_handler::get_protected_paths,
+            file_operations_handler::set_protected_paths,
             file_operations_handler::export_report,
+            snapshot_handler::open_snapshot,
+            snapshot_handler::save_snapshot,
+            snapshot_handler::import_ncdu,
+            snapshot_handler::export_ncdu,
+            snapshot_handler::import_listing,
         ])
         .run(tauri::generate_context!()) 
         .expect("error while running tauri application");
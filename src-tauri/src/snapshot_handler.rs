@@ -0,0 +1,129 @@
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+use std::path::Path;
+
+use diskvader_core::models::{CommandError, ScanData, ScanResults, SharedScanResults};
+use diskvader_core::listing::{self, ListingFormat};
+use diskvader_core::ncdu;
+use diskvader_core::snapshot::ScanSnapshot;
+
+pub(crate) enum DialogMode<'a> {
+    Open,
+    Save(&'a str),
+    Folder,
+}
+
+pub(crate) fn pick_path(app: &AppHandle, filter_name: &str, extensions: &[&str], mode: DialogMode<'_>) -> Result<Option<String>, CommandError> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut dialog = app.dialog().file();
+    if !extensions.is_empty() {
+        dialog = dialog.add_filter(filter_name, extensions);
+    }
+    let callback = move |file_path: Option<tauri_plugin_dialog::FilePath>| {
+        let _ = tx.send(file_path.map(|p| p.to_string()));
+    };
+    match mode {
+        DialogMode::Open => dialog.pick_file(callback),
+        DialogMode::Save(file_name) => dialog.set_file_name(file_name).save_file(callback),
+        DialogMode::Folder => dialog.pick_folder(callback),
+    }
+    Ok(rx.recv()?)
+}
+
+fn resolve_path(app: &AppHandle, path: Option<String>, filter_name: &str, mode: DialogMode<'_>) -> Result<Option<String>, CommandError> {
+    match path {
+        Some(path) => Ok(Some(path)),
+        None => pick_path(app, filter_name, &["json"], mode),
+    }
+}
+
+// Remplace le scan courant par des résultats chargés depuis un fichier, en gardant le journal
+fn load_results(scan_results: &SharedScanResults, loaded: ScanResults, source: &str) -> ScanData {
+    let mut results = scan_results.lock().unwrap();
+    let error_logger = results.error_logger.clone();
+    *results = loaded;
+    results.error_logger = error_logger;
+    results.error_logger.log_info(&format!("Scan data loaded from {}", source), Some(&results.scan_path));
+
+    ScanData {
+        total_files: results.total_files,
+        total_folders: results.total_folders,
+        total_size: results.total_size,
+        free_space: 0,
+        used_percentage: 0.0,
+        scan_time: results.scan_time,
+        scan_path: results.scan_path.clone(),
+        accounting: None,
+    }
+}
+
+#[tauri::command]
+pub async fn open_snapshot(app: AppHandle, path: Option<String>, scan_results: State<'_, SharedScanResults>) -> Result<Option<ScanData>, CommandError> {
+    let Some(path) = resolve_path(&app, path, "DiskVader snapshot", DialogMode::Open)? else {
+        return Ok(None);
+    };
+
+    let snapshot = ScanSnapshot::read_from_file(Path::new(&path))?;
+    println!("Loaded snapshot of {} from {}", snapshot.scan_path, path);
+
+    Ok(Some(load_results(scan_results.inner(), snapshot.into_results(), &path)))
+}
+
+#[tauri::command]
+pub async fn save_snapshot(app: AppHandle, path: Option<String>, scan_results: State<'_, SharedScanResults>) -> Result<Option<String>, CommandError> {
+    let Some(path) = resolve_path(&app, path, "DiskVader snapshot", DialogMode::Save("diskvader-snapshot.json"))? else {
+        return Ok(None);
+    };
+
+    let snapshot = {
+        let results = scan_results.lock().unwrap();
+        ScanSnapshot::from_results(&results)
+    };
+    snapshot.write_to_file(Path::new(&path))?;
+    println!("Snapshot written to {}", path);
+
+    Ok(Some(path))
+}
+
+#[tauri::command]
+pub async fn import_ncdu(app: AppHandle, path: Option<String>, scan_results: State<'_, SharedScanResults>) -> Result<Option<ScanData>, CommandError> {
+    let Some(path) = resolve_path(&app, path, "ncdu export", DialogMode::Open)? else {
+        return Ok(None);
+    };
+
+    let imported = ncdu::import_ncdu_file(Path::new(&path))?;
+    println!("Imported ncdu export of {} from {}", imported.scan_path, path);
+
+    Ok(Some(load_results(scan_results.inner(), imported, &path)))
+}
+
+#[tauri::command]
+pub async fn export_ncdu(app: AppHandle, path: Option<String>, scan_results: State<'_, SharedScanResults>) -> Result<Option<String>, CommandError> {
+    let Some(path) = resolve_path(&app, path, "ncdu export", DialogMode::Save("diskvader-ncdu.json"))? else {
+        return Ok(None);
+    };
+
+    {
+        let results = scan_results.lock().unwrap();
+        ncdu::export_ncdu_file(&results, Path::new(&path))?;
+    }
+    println!("ncdu export written to {}", path);
+
+    Ok(Some(path))
+}
+
+#[tauri::command]
+pub async fn import_listing(app: AppHandle, path: Option<String>, format: Option<ListingFormat>, scan_results: State<'_, SharedScanResults>) -> Result<Option<ScanData>, CommandError> {
+    let path = match path {
+        Some(path) => Some(path),
+        None => pick_path(&app, "File listing", &["txt", "lst", "log", "out"], DialogMode::Open)?,
+    };
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let imported = listing::import_listing_file(Path::new(&path), format)?;
+    println!("Imported listing of {} from {}", imported.scan_path, path);
+
+    Ok(Some(load_results(scan_results.inner(), imported, &path)))
+}
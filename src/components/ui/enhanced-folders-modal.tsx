@@ -16,7 +16,7 @@ const PERFORMANCE_CONFIG = {
 };
 
 interface FolderWithFiles {
-  id: number;
+  id: string;
   name: string;
   path?: string;
   size: number;
@@ -30,7 +30,7 @@ interface FolderWithFiles {
 }
 
 interface FileItem {
-  id: number;
+  id: string;
   name: string;
   path: string;
   size: number;
@@ -59,8 +59,8 @@ const EnhancedFoldersModal: React.FC<EnhancedFoldersModalProps> = ({
   const [sortOrder, setSortOrder] = useState<'asc' | 'desc'>('desc');
   const [currentPage, setCurrentPage] = useState(0);
   const [showAll, setShowAll] = useState(false);
-  const [expandedFolders, setExpandedFolders] = useState<Set<number>>(new Set());
-  const [filesPreviews, setFilesPreviews] = useState<Map<number, boolean>>(new Map());
+  const [expandedFolders, setExpandedFolders] = useState<Set<string>>(new Set());
+  const [filesPreviews, setFilesPreviews] = useState<Map<string, boolean>>(new Map());
 
   // Debounced search term
   const [debouncedSearchTerm, setDebouncedSearchTerm] = useState('');
@@ -188,7 +188,7 @@ const EnhancedFoldersModal: React.FC<EnhancedFoldersModalProps> = ({
   };
 
   // Optimized toggle folder function
-  const toggleFolder = useCallback(async (folderId: number) => {
+  const toggleFolder = useCallback(async (folderId: string) => {
     const isExpanded = expandedFolders.has(folderId);
     
     if (isExpanded) {
@@ -218,7 +218,7 @@ const EnhancedFoldersModal: React.FC<EnhancedFoldersModalProps> = ({
         
         try {
           const files = await invoke('get_folder_files', { 
-            folderPath: folder.path || '' 
+            folderId: folder.id 
           }) as FileItem[];
           
           setFoldersWithFiles(prev => 
@@ -235,7 +235,7 @@ const EnhancedFoldersModal: React.FC<EnhancedFoldersModalProps> = ({
   }, [expandedFolders, foldersWithFiles]);
 
   // Toggle files preview limit
-  const toggleFilesPreview = useCallback((folderId: number) => {
+  const toggleFilesPreview = useCallback((folderId: string) => {
     setFilesPreviews(prev => {
       const newMap = new Map(prev);
       newMap.set(folderId, !newMap.get(folderId));
@@ -1,6 +1,13 @@
 import React, { createContext, useContext, useState, useEffect } from 'react';
 import { errorApi } from '../lib/error-api';
 
+export interface AccountingSummary {
+  deleted_open_size: number;
+  excluded_size: number;
+  unreadable_count: number;
+  unexplained: number;
+}
+
 export interface ScanData {
   total_files: number;
   total_folders: number;
@@ -9,6 +16,7 @@ export interface ScanData {
   used_percentage: number;
   scan_time: number;
   scan_path: string;
+  accounting?: AccountingSummary | null;
   timestamp: number;
 }
 
@@ -398,7 +398,7 @@ const AllFilesModal = ({
             for (const folder of folders.slice(0, 10)) { // Explorer les 10 premiers dossiers
               try {
                 const folderFiles = await invoke('get_folder_files', { 
-                  folderPath: folder.path || folder.name || ''
+                  folderId: folder.id
                 }) as any[];
                 
                 if (folderFiles && folderFiles.length > 0) {
@@ -5,6 +5,7 @@ import { Card, CardContent, CardHeader, CardTitle } from "../components/ui/card"
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "../components/ui/table";
 import { Checkbox } from "../components/ui/checkbox";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "../components/ui/select";
+import { Dialog, DialogContent, DialogDescription, DialogFooter, DialogHeader, DialogTitle } from "../components/ui/dialog";
 import { useToast } from "../components/ui/toast-provider";
 import { invoke } from "@tauri-apps/api/core";
 import { useScanContext } from "../contexts/scan-context";
@@ -17,6 +18,33 @@ interface CleanupSuggestion {
   selected?: boolean;
 }
 
+interface CleanupCandidate {
+  path: string;
+  size: number;
+  is_dir: boolean;
+  reason: string;
+}
+
+interface CleanupPreviewGroup {
+  type: string;
+  size: number;
+  count: number;
+  color_class: string;
+  items: CleanupCandidate[];
+}
+
+interface CleanupPreview {
+  preview_id: string;
+  groups: CleanupPreviewGroup[];
+}
+
+interface CleanupBatch {
+  batch_id: string;
+  freed_size: number;
+  entries: unknown[];
+  failures: { path: string; error: string; needs_confirmation: boolean }[];
+}
+
 const SpaceCleanup = () => {
   const [selectedFiles, setSelectedFiles] = useState<Set<number>>(new Set());
   const [cleanupItems, setCleanupItems] = useState<CleanupSuggestion[]>([]);
@@ -26,6 +54,7 @@ const SpaceCleanup = () => {
   const [filterType, setFilterType] = useState<string>('all');
   const [selectedCleanupFilter, setSelectedCleanupFilter] = useState<string | null>(null);
   const [trashInfo, setTrashInfo] = useState<{size: number, count: number} | null>(null);
+  const [cleanupPreview, setCleanupPreview] = useState<CleanupPreview | null>(null);
   const { addToast } = useToast();
   const { 
     scanData, 
@@ -127,30 +156,67 @@ const SpaceCleanup = () => {
     }
   };
 
+  const selectedCleanupItems = cleanupItems.filter(item => item.selected);
+  const previewGroups = cleanupPreview
+    ? cleanupPreview.groups.filter(group => selectedCleanupItems.some(item => item.type === group.type))
+    : [];
+
+  // Étape 1 : le backend fige la liste exacte des éléments, présentée avant toute suppression
   const handleCleanAllSelected = async () => {
+    if (selectedCleanupItems.length === 0) {
+      addToast({
+        type: 'warning',
+        title: 'No Items Selected',
+        message: 'Please select items to clean.'
+      });
+      return;
+    }
+
     try {
       setCleaningUp(true);
-      const selectedItems = cleanupItems.filter(item => item.selected);
-      if (selectedItems.length === 0) {
-        addToast({
-          type: 'warning',
-          title: 'No Items Selected',
-          message: 'Please select items to clean.'
-        });
-        return;
-      }
+      const preview = await invoke("preview_cleanup") as CleanupPreview;
+      setCleanupPreview(preview);
+    } catch (error) {
+      console.error('Error previewing cleanup:', error);
+      addToast({
+        type: 'error',
+        title: 'Preview Failed',
+        message: 'Failed to list the items to clean. Please try again.'
+      });
+    } finally {
+      setCleaningUp(false);
+    }
+  };
 
-      await invoke("clean_selected_items", { items: selectedItems });
+  // Étape 2 : exécution de l'aperçu validé
+  const handleConfirmCleanup = async () => {
+    if (!cleanupPreview) return;
+
+    try {
+      setCleaningUp(true);
+      const batch = await invoke("clean_selected_items", {
+        previewId: cleanupPreview.preview_id,
+        items: selectedCleanupItems,
+      }) as CleanupBatch;
+      setCleanupPreview(null);
       
       // Refresh cleanup suggestions
       const newCleanupData = await invoke("get_cleanup_suggestions");
-      setCleanupItems(newCleanupData as any[]);
+      setCleanupItems((newCleanupData as CleanupSuggestion[]).map(item => ({ ...item, selected: false })));
       
-      addToast({
-        type: 'success',
-        title: 'Cleanup Completed',
-        message: `Successfully cleaned ${selectedItems.length} items.`
-      });
+      if (batch.failures.length > 0) {
+        addToast({
+          type: 'warning',
+          title: 'Cleanup Partially Completed',
+          message: `Cleaned ${batch.entries.length} items (${formatSize(batch.freed_size)}), ${batch.failures.length} skipped: ${batch.failures[0].error}`
+        });
+      } else {
+        addToast({
+          type: 'success',
+          title: 'Cleanup Completed',
+          message: `Successfully cleaned ${batch.entries.length} items (${formatSize(batch.freed_size)}).`
+        });
+      }
     } catch (error) {
       console.error('Error cleaning items:', error);
       addToast({
@@ -460,6 +526,57 @@ const SpaceCleanup = () => {
         </div>
       </div>
 
+      {/* Cleanup Preview */}
+      <Dialog open={cleanupPreview !== null} onOpenChange={(open) => { if (!open) setCleanupPreview(null); }}>
+        <DialogContent className="max-w-3xl">
+          <DialogHeader>
+            <DialogTitle>Review Cleanup</DialogTitle>
+            <DialogDescription>
+              {previewGroups.reduce((sum, group) => sum + group.count, 0).toLocaleString()} items
+              ({formatSize(previewGroups.reduce((sum, group) => sum + group.size, 0))}) will be moved to the trash.
+            </DialogDescription>
+          </DialogHeader>
+          <div className="max-h-96 overflow-y-auto space-y-4">
+            {previewGroups.map(group => (
+              <div key={group.type}>
+                <div className="flex justify-between font-medium text-gray-900 mb-1">
+                  <span>{group.type}</span>
+                  <span>{formatSize(group.size)}</span>
+                </div>
+                {group.items.length === 0 ? (
+                  <p className="text-sm text-gray-500">Nothing to clean.</p>
+                ) : (
+                  <ul className="text-sm divide-y divide-gray-100">
+                    {group.items.map(item => (
+                      <li key={item.path} className="flex justify-between py-1 space-x-4">
+                        <div className="min-w-0">
+                          <div className="text-gray-900 truncate">{item.path}</div>
+                          <div className="text-xs text-gray-500">{item.reason}</div>
+                        </div>
+                        <span className="text-gray-600 whitespace-nowrap">{formatSize(item.size)}</span>
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </div>
+            ))}
+          </div>
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setCleanupPreview(null)} disabled={cleaningUp}>
+              Cancel
+            </Button>
+            <Button
+              onClick={handleConfirmCleanup}
+              className="bg-red-600 text-white hover:bg-red-700"
+              disabled={cleaningUp || previewGroups.every(group => group.items.length === 0)}
+            >
+              {cleaningUp ? <Loader2 className="w-4 h-4 mr-2 animate-spin" /> : <Trash2 className="w-4 h-4 mr-2" />}
+              Confirm Cleanup
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+
       {/* Quick Cleanup Suggestions */}
       <div className="mb-8">
         <div className="flex justify-between items-center mb-4">